@@ -0,0 +1,307 @@
+//! This strategy fills in the guess one slot at a time, and gives up on a partial guess
+//! as soon as any clue can no longer be satisfied by it, no matter what goes in the remaining slots.
+//!
+//! It finds the same solutions in the same order as the brute-force strategy, but skips
+//! most of the combinations that strategy would have to check one by one.
+
+use crate::{
+    Clue, Peg, Pegs, Puzzle, Rules, SolveError, bruteforce::alphabet, check_clues,
+    rules::distinct_count,
+};
+
+/// # Errors
+///
+/// If `clues` don't make up a valid puzzle
+pub fn solve_backtrack(free_pegs: &Pegs, clues: &[Clue]) -> Result<Option<String>, SolveError> {
+    let mut raw = solve_backtrack_raw(free_pegs, clues)?;
+    Ok(raw.next().and_then(|guess| String::from_utf8(guess).ok()))
+}
+
+/// Solves `clues` with the game's rules for their size.
+///
+/// Solutions come in ascending lexicographic order of their peg values.
+/// See [`Puzzle`](crate::Puzzle) for more control.
+///
+/// # Errors
+///
+/// If `clues` don't make up a valid puzzle
+pub fn solve_backtrack_raw<'a>(
+    free_pegs: &'a Pegs,
+    clues: &'a [Clue],
+) -> Result<impl Iterator<Item = Vec<u8>> + 'a, SolveError> {
+    let size = clues.first().map_or(0, |clue| clue.pegs.len());
+    check_clues(size, clues)?;
+    Ok(Backtrack::new(
+        free_pegs,
+        clues,
+        size,
+        Rules::for_size(size),
+    ))
+}
+
+/// Counts the solutions of `puzzle` without collecting them.
+///
+/// With a `cap`, counting stops early once `cap` solutions have been found.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn count_solutions(puzzle: &Puzzle, cap: Option<u64>) -> Result<u64, SolveError> {
+    puzzle.validate()?;
+    Ok(count(puzzle, cap))
+}
+
+/// Like [`count_solutions`], but without validating, so it also works without clues
+pub(crate) fn count(puzzle: &Puzzle, cap: Option<u64>) -> u64 {
+    let mut search = Backtrack::for_puzzle(puzzle);
+    let mut count = 0;
+    while cap.is_none_or(|cap| count < cap) && search.advance() {
+        count += 1;
+    }
+    count
+}
+
+/// Which pegs each slot can hold in the solutions of `puzzle`.
+///
+/// For every slot, lists the pegs that appear there in at least one solution,
+/// in ascending order, along with the number of solutions that have them there.
+/// The counts of any one slot add up to the number of solutions.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn slot_possibilities(puzzle: &Puzzle) -> Result<Vec<Vec<(Peg, u64)>>, SolveError> {
+    puzzle.validate()?;
+    let mut search = Backtrack::for_puzzle(puzzle);
+    let mut counts = vec![vec![0; search.alphabet.len()]; puzzle.size];
+    while search.advance() {
+        for (slot, &idx) in counts.iter_mut().zip(&search.path) {
+            slot[idx] += 1;
+        }
+    }
+    Ok(counts
+        .into_iter()
+        .map(|slot| {
+            search
+                .alphabet
+                .iter()
+                .zip(slot)
+                .filter(|&(_, count)| count != 0)
+                .map(|(&peg, count)| (peg, count))
+                .collect()
+        })
+        .collect())
+}
+
+/// Depth-first search over partial guesses
+pub(crate) struct Backtrack<'a> {
+    alphabet: Vec<Peg>,
+    clues: &'a [Clue],
+    size: usize,
+    rules: Rules,
+    /// Alphabet index for each filled slot
+    path: Vec<usize>,
+    /// The pegs `path` refers to
+    pub(crate) guess: Vec<Peg>,
+    /// Hearts and common pegs (hearts + dots) between the partial guess and each clue.
+    ///
+    /// Indexed by `depth * clues.len() + clue`, where depth is the number of filled slots.
+    counts: Vec<(u8, u8)>,
+    /// How many clues a solution may contradict
+    tolerance: usize,
+    /// For each slot, which alphabet indices it may hold, if restricted
+    domains: Option<&'a [Vec<bool>]>,
+    started: bool,
+}
+
+impl<'a> Backtrack<'a> {
+    pub(crate) fn new(free_pegs: &Pegs, clues: &'a [Clue], size: usize, rules: Rules) -> Self {
+        Self {
+            alphabet: alphabet(free_pegs, clues),
+            clues,
+            size,
+            rules,
+            path: Vec::with_capacity(size),
+            guess: Vec::with_capacity(size),
+            counts: vec![(0, 0); (size + 1) * clues.len()],
+            tolerance: 0,
+            domains: None,
+            started: false,
+        }
+    }
+    /// Searches the solutions of `puzzle`, without validating it
+    pub(crate) fn for_puzzle(puzzle: &'a Puzzle) -> Self {
+        Self::new(&puzzle.alphabet(), &puzzle.clues, puzzle.size, puzzle.rules)
+    }
+    /// Also accepts guesses that contradict up to `tolerance` clues
+    pub(crate) fn with_tolerance(mut self, tolerance: usize) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+    /// Only tries the pegs `domains` allows for each slot, by alphabet index
+    pub(crate) fn with_domains(mut self, domains: &'a [Vec<bool>]) -> Self {
+        self.domains = Some(domains);
+        self
+    }
+    /// Moves on to the next solution, and returns whether there was one.
+    ///
+    /// The solution can then be read from `self.guess`.
+    pub(crate) fn advance(&mut self) -> bool {
+        if self.started {
+            self.bump();
+        } else {
+            self.started = true;
+            self.path.push(0);
+        }
+        loop {
+            let Some(&last) = self.path.last() else {
+                return false;
+            };
+            if last == self.alphabet.len() {
+                self.path.pop();
+                self.bump();
+                continue;
+            }
+            if !self.fill_last() {
+                self.bump();
+                continue;
+            }
+            if self.path.len() == self.size {
+                return true;
+            }
+            self.path.push(0);
+        }
+    }
+    /// Moves the last filled slot on to the next peg
+    fn bump(&mut self) {
+        if let Some(last) = self.path.last_mut() {
+            *last += 1;
+        }
+    }
+    /// Puts the peg chosen by the last path entry into the guess, and returns whether
+    /// it follows the rules and every clue, save at most `tolerance`, can still be satisfied.
+    ///
+    /// A clue that can't be satisfied by a partial guess stays that way for the full guess,
+    /// so the number of such clues never goes down as the guess is filled in.
+    fn fill_last(&mut self) -> bool {
+        let depth = self.path.len() - 1;
+        if let Some(domains) = self.domains
+            && !domains[depth][self.path[depth]]
+        {
+            return false;
+        }
+        let peg = self.alphabet[self.path[depth]];
+        self.guess.truncate(depth);
+        if self.rules.distinct_neighbours
+            && self
+                .rules
+                .layout
+                .earlier_neighbours(depth)
+                .any(|slot| self.guess[slot] == peg)
+        {
+            return false;
+        }
+        let already_in_guess = occurrences(&self.guess, peg);
+        let remaining = self.size - depth - 1;
+        if self
+            .rules
+            .max_repeats
+            .is_some_and(|max| already_in_guess >= usize::from(max))
+        {
+            return false;
+        }
+        if let Some(colors) = self.rules.required_colors {
+            let distinct = distinct_count(&self.guess) + usize::from(already_in_guess == 0);
+            if distinct > usize::from(colors) || distinct + remaining < usize::from(colors) {
+                return false;
+            }
+        }
+        self.guess.push(peg);
+        let n_clues = self.clues.len();
+        let mut contradicted = 0;
+        for (i, clue) in self.clues.iter().enumerate() {
+            let (mut hearts, mut common) = self.counts[depth * n_clues + i];
+            if clue.pegs[depth] == peg {
+                hearts += 1;
+            }
+            if occurrences(&clue.pegs, peg) > already_in_guess {
+                common += 1;
+            }
+            let want_hearts = usize::from(clue.indicator.hearts);
+            let want_common = want_hearts + usize::from(clue.indicator.dots);
+            let (h, c) = (usize::from(hearts), usize::from(common));
+            if h > want_hearts
+                || h + remaining < want_hearts
+                || c > want_common
+                || c + remaining < want_common
+            {
+                contradicted += 1;
+                if contradicted > self.tolerance {
+                    return false;
+                }
+            }
+            self.counts[(depth + 1) * n_clues + i] = (hearts, common);
+        }
+        true
+    }
+}
+
+/// How many times `peg` appears in `pegs`
+// Peg lists are far too short for anything fancier to pay off
+#[expect(clippy::naive_bytecount)]
+pub(crate) fn occurrences(pegs: &Pegs, peg: Peg) -> usize {
+    pegs.iter().filter(|&&p| p == peg).count()
+}
+
+impl Iterator for Backtrack<'_> {
+    type Item = Vec<Peg>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance().then(|| self.guess.clone())
+    }
+}
+
+#[test]
+fn test_same_as_bruteforce() {
+    use crate::{Puzzle, parse_shortform};
+    for (input, free_pegs) in [
+        ("ccprg12 cyppc11 crycg13 rccgg13 yrccc03", ""),
+        ("ccprg12 cyppc11 crycg13", "w"),
+        ("rgb10 bby01", ""),
+        ("rgbyopb11 ybgoryp12", "wk"),
+        ("rgbyopb34", ""),
+    ] {
+        let mut puzzle = Puzzle::from_clues(parse_shortform(input));
+        puzzle.free_pegs = free_pegs.as_bytes().to_vec();
+        let bruteforce: Vec<_> = puzzle.solutions().unwrap().collect();
+        let backtrack: Vec<_> = puzzle.solutions_backtrack().unwrap().collect();
+        assert!(!bruteforce.is_empty(), "{input}");
+        assert!(bruteforce.is_sorted(), "{input}");
+        assert_eq!(bruteforce, backtrack, "{input}");
+        assert_eq!(
+            count_solutions(&puzzle, None),
+            Ok(bruteforce.len() as u64),
+            "{input}"
+        );
+        assert_eq!(count_solutions(&puzzle, Some(1)), Ok(1), "{input}");
+    }
+}
+
+#[test]
+fn test_slot_possibilities() {
+    use crate::parse_shortform;
+    let puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13"));
+    let solutions: Vec<_> = puzzle.solutions_backtrack().unwrap().collect();
+    let possibilities = slot_possibilities(&puzzle).unwrap();
+    assert_eq!(possibilities.len(), 5);
+    for (slot, pegs) in possibilities.iter().enumerate() {
+        assert_eq!(pegs.iter().map(|&(_, n)| n).sum::<u64>(), 10);
+        for &(peg, n) in pegs {
+            let expected = solutions.iter().filter(|sol| sol[slot] == peg).count();
+            assert_eq!(n, expected as u64);
+        }
+    }
+    assert_eq!(
+        possibilities[0],
+        [(b'c', 4), (b'g', 1), (b'r', 3), (b'y', 2)]
+    );
+}
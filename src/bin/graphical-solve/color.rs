@@ -90,6 +90,17 @@ pub const SCHEMES: [Scheme; 15] = [
     },
 ];
 
+/// The short form letter of each scheme, in the same order as [`SCHEMES`]
+pub const LETTERS: [u8; SCHEMES.len()] = *b"rygobpsalmcwken";
+
+/// Looks up the scheme index for a short form letter
+pub fn peg_for_letter(letter: u8) -> Option<u8> {
+    LETTERS
+        .iter()
+        .position(|&l| l == letter.to_ascii_lowercase())
+        .and_then(|idx| u8::try_from(idx).ok())
+}
+
 const fn cdiv(c255: u8) -> f32 {
     c255 as f32 / 255.
 }
@@ -9,7 +9,11 @@ use util::ValLooper;
 use button::{ImgButton, SimpleButton};
 use macroquad::prelude::*;
 use miniquad::{BlendFactor, BlendState, BlendValue, Equation};
-use mmsolv::{Clue, Indicator, solve_bruteforce_raw};
+use mmsolv::{
+    Clue, Conflict, Indicator, Layout, Puzzle, Rules, SolveTask, SolverSession, SuggestStrategy,
+    find_conflict, group_solutions, suggest_guess,
+};
+use std::cmp::Reverse;
 
 const PEG_SIZE: f32 = 64.0;
 
@@ -108,6 +112,8 @@ struct ClueRow {
     dot_rem_but: ImgButton,
     heart_add_but: ImgButton,
     heart_rem_but: ImgButton,
+    /// Background color of the slots, for pointing out problematic clues
+    highlight: Option<Color>,
 }
 
 const HEART_BUT_DOWN_COL: Color = Color {
@@ -127,6 +133,7 @@ impl ClueRow {
             dot_rem_but: ImgButton::new(src_rects::MINUS, 0., 0., GRAY, LIGHTGRAY),
             heart_add_but: ImgButton::new(src_rects::PLUS, 0., 0., RED, HEART_BUT_DOWN_COL),
             heart_rem_but: ImgButton::new(src_rects::MINUS, 0., 0., RED, HEART_BUT_DOWN_COL),
+            highlight: None,
         }
     }
 }
@@ -138,47 +145,83 @@ const BOX_SIZE: f32 = PEG_SIZE + BOX_PADDING_INNER;
 const BOX_VERT_DISTANCE: f32 = 8.;
 const BOX_HORIZ_DISTANCE: f32 = 8.;
 
-const SEVEN_OFFSETS: [(f32, f32); 7] = [
-    (0.5, 0.0),
-    (1.5, 0.0),
-    (0.0, 1.0),
-    (1.0, 1.0),
-    (2.0, 1.0),
-    (0.5, 2.0),
-    (1.5, 2.0),
-];
-
-fn clue_rect(row: usize, col: usize, seven_peg: bool, y_scroll_offset: f32) -> Rect {
-    if seven_peg {
-        const SEVEN_PEG_PADDING: f32 = 8.0;
-        Rect {
-            x: CLUE_ROW_X_OFFSET + SEVEN_OFFSETS[col].0 * (BOX_SIZE + BOX_HORIZ_DISTANCE),
-            y: CLUE_ROW_Y_OFFSET
-                + row as f32 * ((BOX_SIZE + BOX_VERT_DISTANCE + SEVEN_PEG_PADDING) * 3.)
-                + (SEVEN_OFFSETS[col].1 * (BOX_SIZE + BOX_VERT_DISTANCE))
-                + y_scroll_offset,
-            w: BOX_SIZE,
-            h: BOX_SIZE,
-        }
-    } else {
-        Rect {
-            x: CLUE_ROW_X_OFFSET + col as f32 * (BOX_SIZE + BOX_HORIZ_DISTANCE),
-            y: CLUE_ROW_Y_OFFSET + row as f32 * (BOX_SIZE + BOX_VERT_DISTANCE) + y_scroll_offset,
-            w: BOX_SIZE,
-            h: BOX_SIZE,
+/// The peg counts to pick from, one for each of the solver's built-in layouts
+const PEG_COUNTS: [u8; mmsolv::SUPPORTED_PEG_COUNTS.len()] = {
+    let mut counts = [0; mmsolv::SUPPORTED_PEG_COUNTS.len()];
+    let mut i = 0;
+    while i < counts.len() {
+        // Layouts are nowhere near 256 slots
+        #[expect(clippy::cast_possible_truncation)]
+        {
+            counts[i] = mmsolv::SUPPORTED_PEG_COUNTS[i] as u8;
         }
+        i += 1;
+    }
+    counts
+};
+
+/// The layout of a puzzle with `n_pegs` pegs, which is one of [`PEG_COUNTS`]
+fn layout_for(n_pegs: u8) -> Layout {
+    Layout::for_size(n_pegs.into()).unwrap_or_default()
+}
+
+/// Position of `slot` in `layout`, in peg widths
+fn slot_offset(layout: Layout, slot: usize) -> (f32, f32) {
+    let (x, y) = layout.positions[slot];
+    (f32::from(x) / 2., f32::from(y) / 2.)
+}
+
+/// How many rows of slots `layout` takes up
+fn layout_rows(layout: Layout) -> f32 {
+    f32::from(layout.extent().1) / 2.
+}
+
+/// The slot furthest up, and of those the one furthest right
+fn top_right_slot(layout: Layout) -> usize {
+    (0..layout.len())
+        .min_by_key(|&i| {
+            let (x, y) = layout.positions[i];
+            (y, Reverse(x))
+        })
+        .unwrap_or(0)
+}
+
+/// The slot furthest down, and of those the one furthest left
+fn bottom_left_slot(layout: Layout) -> usize {
+    (0..layout.len())
+        .max_by_key(|&i| {
+            let (x, y) = layout.positions[i];
+            (y, Reverse(x))
+        })
+        .unwrap_or(0)
+}
+
+fn clue_rect(row: usize, col: usize, layout: Layout, y_scroll_offset: f32) -> Rect {
+    // Clues that take up several rows need some room between them
+    const MULTI_ROW_PADDING: f32 = 8.0;
+    let (x, y) = slot_offset(layout, col);
+    let rows = layout_rows(layout);
+    let padding = if rows > 1. { MULTI_ROW_PADDING } else { 0. };
+    Rect {
+        x: CLUE_ROW_X_OFFSET + x * (BOX_SIZE + BOX_HORIZ_DISTANCE),
+        y: CLUE_ROW_Y_OFFSET
+            + row as f32 * ((BOX_SIZE + BOX_VERT_DISTANCE + padding) * rows)
+            + y * (BOX_SIZE + BOX_VERT_DISTANCE)
+            + y_scroll_offset,
+        w: BOX_SIZE,
+        h: BOX_SIZE,
     }
 }
 
 fn clue_rects(
     rows: &[ClueRow],
-    seven_peg: bool,
+    layout: Layout,
     y_scroll_offset: f32,
 ) -> impl Iterator<Item = (Rect, usize, usize)> + '_ {
     rows.iter().enumerate().flat_map(move |(row_num, row)| {
         row.slots.iter().enumerate().map(move |(col, _)| {
             (
-                clue_rect(row_num, col, seven_peg, y_scroll_offset),
+                clue_rect(row_num, col, layout, y_scroll_offset),
                 row_num,
                 col,
             )
@@ -195,16 +238,19 @@ fn draw_clue_row(
     my: f32,
     picked_color: Option<Color>,
     tex: &Texture2D,
-    seven_peg: bool,
+    layout: Layout,
     y_scroll_offset: f32,
     mat: &Material,
 ) {
     for (i, slot) in row.slots.iter().enumerate() {
-        let rect = clue_rect(row_num, i, seven_peg, y_scroll_offset);
-        if let Some(picked_color) = picked_color {
-            if rect.contains(Vec2::new(mx, my)) {
-                draw_rectangle(rect.x, rect.y, rect.w, rect.h, picked_color);
-            }
+        let rect = clue_rect(row_num, i, layout, y_scroll_offset);
+        if let Some(highlight) = row.highlight {
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, highlight);
+        }
+        if let Some(picked_color) = picked_color
+            && rect.contains(Vec2::new(mx, my))
+        {
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, picked_color);
         }
         if let Some(pegid) = *slot {
             draw_peg(
@@ -219,8 +265,7 @@ fn draw_clue_row(
         }
         draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, RED);
     }
-    let last_rect_idx = if seven_peg { 1 } else { row.slots.len() - 1 };
-    let last_rect = clue_rect(row_num, last_rect_idx, seven_peg, y_scroll_offset);
+    let last_rect = clue_rect(row_num, top_right_slot(layout), layout, y_scroll_offset);
     row.heart_add_but.rect.x = last_rect.x + 4. + BOX_SIZE;
     row.heart_add_but.rect.y = last_rect.y + 4.;
     row.heart_rem_but.rect.x = last_rect.x + 4. + BOX_SIZE + 32.;
@@ -266,7 +311,7 @@ fn draw_clue_rows(
     my: f32,
     picked_color: Option<Color>,
     tex: &Texture2D,
-    seven_peg: bool,
+    layout: Layout,
     y_scroll_offset: f32,
     mat: &Material,
 ) {
@@ -278,35 +323,289 @@ fn draw_clue_rows(
             my,
             picked_color,
             tex,
-            seven_peg,
+            layout,
             y_scroll_offset,
             mat,
         );
     }
 }
 
-fn conv_mmsolv(rows: &[ClueRow]) -> Result<Vec<Clue>, String> {
-    let mut clues = Vec::new();
+/// Rules picked with the buttons, on top of the game's rules. 0 means no limit.
+struct RuleOptions {
+    max_repeats: ValLooper<'static>,
+    colors: ValLooper<'static>,
+    /// Whether every color can be in the solution, not just those in clues and free pegs
+    any_color: bool,
+}
+
+impl RuleOptions {
+    fn new() -> Self {
+        Self {
+            max_repeats: ValLooper::new(&[0, 1, 2, 3]),
+            colors: ValLooper::new(&[0, 1, 2, 3, 4, 5, 6, 7]),
+            any_color: false,
+        }
+    }
+    /// Moves on to the next color count that `n_pegs` pegs can have
+    fn next_colors(&mut self, n_pegs: u8) {
+        self.colors.go_next();
+        while self.colors.value() > n_pegs {
+            self.colors.go_next();
+        }
+    }
+    /// Goes back to any number of colors if `n_pegs` pegs can't have the current one
+    fn fit_colors(&mut self, n_pegs: u8) {
+        if self.colors.value() > n_pegs {
+            self.colors.set(0);
+        }
+    }
+    fn apply(&self, rules: &mut Rules) {
+        rules.max_repeats = Some(self.max_repeats.value()).filter(|&n| n != 0);
+        rules.required_colors = Some(self.colors.value()).filter(|&n| n != 0);
+    }
+    fn repeats_text(&self) -> String {
+        match self.max_repeats.value() {
+            0 => "Repeats: any".into(),
+            1 => "No repeats".into(),
+            n => format!("Repeats: {n}"),
+        }
+    }
+    fn colors_text(&self) -> String {
+        match self.colors.value() {
+            0 => "Colors: any".into(),
+            n => format!("Colors: {n}"),
+        }
+    }
+    fn palette_text(&self) -> String {
+        if self.any_color {
+            "Any color".into()
+        } else {
+            "Seen colors".into()
+        }
+    }
+}
+
+/// The clue `row` makes, or `None` if it has an empty slot
+fn row_clue(row: &ClueRow) -> Option<Clue> {
+    Some(Clue {
+        indicator: Indicator {
+            dots: row.dots,
+            hearts: row.hearts,
+        },
+        pegs: row.slots.iter().copied().collect::<Option<_>>()?,
+    })
+}
+
+fn conv_mmsolv(
+    rows: &[ClueRow],
+    n_pegs: u8,
+    free_pegs: &[u8],
+    options: &RuleOptions,
+) -> Result<Puzzle, String> {
+    let clues = rows
+        .iter()
+        .map(row_clue)
+        .collect::<Option<_>>()
+        .ok_or("Empty slot somewhere")?;
+    Ok(puzzle_with_clues(clues, n_pegs, free_pegs, options))
+}
+
+fn puzzle_with_clues(
+    clues: Vec<Clue>,
+    n_pegs: u8,
+    free_pegs: &[u8],
+    options: &RuleOptions,
+) -> Puzzle {
+    let mut puzzle = Puzzle::new(n_pegs.into(), clues);
+    puzzle.free_pegs = free_pegs.to_vec();
+    options.apply(&mut puzzle.rules);
+    if options.any_color {
+        // `SCHEMES` isn't longer than `u8::MAX`
+        #[expect(clippy::cast_possible_truncation)]
+        let palette = (0..color::SCHEMES.len() as u8).collect();
+        puzzle.palette = Some(palette);
+    }
+    puzzle
+}
+
+/// Brings the clues of `session` up to date with `puzzle`, and returns whether they changed.
+///
+/// Only the clues from the first changed one on are queued again, unless the size, rules
+/// or pegs in play changed. Either way the candidates are left to be worked out a bit each
+/// frame. If `puzzle` can't be solved, or has too many candidates to keep up with, there's
+/// no session.
+fn sync_session(session: &mut Option<SolverSession>, puzzle: &Puzzle) -> bool {
+    if !puzzle
+        .candidates()
+        .is_ok_and(|candidates| candidates.len() <= MAX_LIVE_CANDIDATES)
+    {
+        return session.take().is_some();
+    }
+    if let Some(current) = session
+        && current.size() == puzzle.size
+        && current.rules() == puzzle.rules
+        && current.alphabet() == puzzle.alphabet()
+    {
+        if current.clues() == puzzle.clues {
+            return false;
+        }
+        if current.queue_clues(&puzzle.clues).is_err() {
+            *session = None;
+        }
+        return true;
+    }
+    let had_session = session.is_some();
+    *session = puzzle.validate().ok().and_then(|()| {
+        let mut fresh = SolverSession::new(puzzle.size, &puzzle.alphabet(), puzzle.rules);
+        fresh.queue_clues(&puzzle.clues).ok()?;
+        Some(fresh)
+    });
+    had_session || session.is_some()
+}
+
+/// Converts scheme index pegs to their short form letters
+fn lettered(mut clues: Vec<Clue>) -> Vec<Clue> {
+    for clue in &mut clues {
+        for peg in &mut clue.pegs {
+            *peg = color::LETTERS[*peg as usize];
+        }
+    }
+    clues
+}
+
+/// Short form input that couldn't be imported
+struct InputError {
+    input: String,
+    /// Byte offset of the character to underline
+    offset: usize,
+    msg: String,
+}
+
+/// The clue rows and free pegs of the short form `input`
+fn import_shortform(
+    input: &str,
+    n_pegs: &mut ValLooper,
+) -> Result<(Vec<ClueRow>, Vec<u8>), InputError> {
+    let err = |offset, msg| InputError {
+        input: input.to_owned(),
+        offset,
+        msg,
+    };
+    let puzzle = mmsolv::parse_shortform_puzzle(input).map_err(|e| err(e.offset, e.to_string()))?;
+    let clues = puzzle.clues;
+    if let Some(offset) = input
+        .bytes()
+        .position(|b| b.is_ascii_alphabetic() && color::peg_for_letter(b).is_none())
+    {
+        return Err(err(
+            offset,
+            format!("Unknown peg '{}'", char::from(input.as_bytes()[offset])),
+        ));
+    }
+    let Some(first) = clues.first() else {
+        return Err(err(0, "No clues".into()));
+    };
+    if !u8::try_from(first.pegs.len()).is_ok_and(|len| n_pegs.set(len)) {
+        return Err(err(
+            0,
+            mmsolv::SolveError::UnsupportedPegCount(first.pegs.len()).to_string(),
+        ));
+    }
+    let rows = clues
+        .iter()
+        .map(|clue| {
+            let mut row = ClueRow::new(n_pegs.value());
+            for (slot, &peg) in row.slots.iter_mut().zip(&clue.pegs) {
+                *slot = color::peg_for_letter(peg);
+            }
+            row.hearts = clue.indicator.hearts;
+            row.dots = clue.indicator.dots;
+            row
+        })
+        .collect();
+    let free_pegs = puzzle
+        .free_pegs
+        .iter()
+        .filter_map(|&letter| color::peg_for_letter(letter))
+        .collect();
+    Ok((rows, free_pegs))
+}
+
+/// Explains why the guess in `input` is or isn't a solution of `puzzle`, line by line
+fn check_guess(input: &str, puzzle: &Puzzle) -> Result<Vec<String>, InputError> {
+    let err = |offset, msg| InputError {
+        input: input.to_owned(),
+        offset,
+        msg,
+    };
+    let guess = input
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| {
+            color::peg_for_letter(b)
+                .ok_or_else(|| err(i, format!("Unknown peg '{}'", char::from(b))))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let explanation =
+        mmsolv::explain_guess(&guess, puzzle).map_err(|e| err(input.len(), e.to_string()))?;
+    let mut lines: Vec<String> = explanation.to_string().lines().map(str::to_owned).collect();
+    lines.push(
+        if explanation.ok() {
+            "It's a solution"
+        } else {
+            "It's not a solution"
+        }
+        .into(),
+    );
+    Ok(lines)
+}
+
+fn draw_report(lines: &[String], x: f32, y: f32) {
+    for (i, line) in lines.iter().enumerate() {
+        let color = if line.starts_with("FAIL") { RED } else { BLACK };
+        draw_text(line, x, y + i as f32 * 28.0, 28.0, color);
+    }
+}
+
+fn draw_input_error(err: &InputError, x: f32, y: f32) {
+    const FONT_SIZE: u16 = 32;
+    draw_text(&err.input, x, y, f32::from(FONT_SIZE), BLACK);
+    let before = err.input.get(..err.offset).unwrap_or(&err.input);
+    let bad = err
+        .input
+        .get(err.offset..)
+        .and_then(|rest| rest.chars().next())
+        .unwrap_or(' ');
+    let underline_x = x + measure_text(before, None, FONT_SIZE, 1.0).width;
+    let underline_w = measure_text(&bad.to_string(), None, FONT_SIZE, 1.0)
+        .width
+        .max(12.0);
+    draw_line(
+        underline_x,
+        y + 6.0,
+        underline_x + underline_w,
+        y + 6.0,
+        3.0,
+        RED,
+    );
+    draw_text(&err.msg, x, y + 36.0, f32::from(FONT_SIZE), RED);
+}
+
+/// Marks the clues that contradict each other pink, and the likely culprits orange
+fn highlight_conflict(rows: &mut [ClueRow], conflict: &Conflict) {
+    for &i in &conflict.minimal_subset {
+        rows[i].highlight = Some(PINK);
+    }
+    for &i in &conflict.culprits {
+        rows[i].highlight = Some(ORANGE);
+    }
+}
+
+/// Takes the conflict colors off every row, once they might not be right anymore
+fn clear_highlights(rows: &mut [ClueRow]) {
     for row in rows {
-        let clue = Clue {
-            indicator: Indicator {
-                dots: row.dots,
-                hearts: row.hearts,
-            },
-            pegs: {
-                let mut pegs = Vec::new();
-                for slot in &row.slots {
-                    let Some(val) = slot else {
-                        return Err("Empty slot somewhere".into());
-                    };
-                    pegs.push(*val);
-                }
-                pegs.into_boxed_slice()
-            },
-        };
-        clues.push(clue);
+        row.highlight = None;
     }
-    Ok(clues)
 }
 
 fn repos_solve_but(but: &mut SimpleButton, bottom_rect: Rect) {
@@ -316,6 +615,90 @@ fn repos_solve_but(but: &mut SimpleButton, bottom_rect: Rect) {
 
 const MAIN_AREA_START_X: f32 = CLUE_ROW_X_OFFSET - 8.0;
 const MAX_SOLUTIONS: usize = 99;
+/// Above this many solutions, suggesting a next guess takes too long to be worth it
+const MAX_SOLUTIONS_TO_SUGGEST: u64 = 1000;
+/// Above this many candidates, a solve is run a bit each frame instead of kept up to date
+const MAX_LIVE_CANDIDATES: usize = 5_000_000;
+/// How many candidates a solve, or the session catching up, checks each frame, so the
+/// window stays responsive
+const SOLVE_BUDGET_PER_FRAME: usize = 100_000;
+
+/// Everything shown about a solved puzzle
+struct Solved {
+    count: u64,
+    possibilities: Vec<Vec<(u8, u64)>>,
+    /// The first [`MAX_SOLUTIONS`] solutions
+    solutions: Vec<Vec<u8>>,
+    suggestion: Option<Vec<u8>>,
+    conflict: Option<Conflict>,
+    /// Number of solution groups, if some colors might be in the solution without being in a clue
+    patterns: Option<usize>,
+}
+
+/// Works out what to show for `puzzle`, from which pegs its solutions have in each slot,
+/// and the first of them
+fn solved(
+    puzzle: &Puzzle,
+    possibilities: Vec<Vec<(u8, u64)>>,
+    solutions: Vec<Vec<u8>>,
+) -> Result<Solved, String> {
+    let count = solution_count(&possibilities);
+    let suggestion = if (2..=MAX_SOLUTIONS_TO_SUGGEST).contains(&count) {
+        suggest_guess(puzzle, SuggestStrategy::Minimax)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next()
+            .map(|score| score.guess)
+    } else {
+        None
+    };
+    // Finding the conflict solves the puzzle over and over, which would freeze the window
+    // for as long as the solve that was spread over frames
+    let conflict = if count == 0
+        && puzzle
+            .candidates()
+            .is_ok_and(|candidates| candidates.len() <= MAX_LIVE_CANDIDATES)
+    {
+        find_conflict(puzzle).map_err(|e| e.to_string())?
+    } else {
+        None
+    };
+    // Grouping goes through every solution, like suggesting does
+    let patterns = if puzzle.palette.is_some() && (2..=MAX_SOLUTIONS_TO_SUGGEST).contains(&count) {
+        Some(group_solutions(puzzle).map_err(|e| e.to_string())?.len())
+    } else {
+        None
+    };
+    Ok(Solved {
+        count,
+        possibilities,
+        solutions,
+        suggestion,
+        conflict,
+        patterns,
+    })
+}
+
+/// Which pegs each slot holds in `solutions`, and how often,
+/// like [`SolverSession::slot_possibilities`]
+fn possibilities_of(solutions: &[Vec<u8>], n_pegs: usize) -> Vec<Vec<(u8, u64)>> {
+    let mut counts = vec![[0; 256]; n_pegs];
+    for solution in solutions {
+        for (slot, &peg) in counts.iter_mut().zip(solution) {
+            slot[usize::from(peg)] += 1;
+        }
+    }
+    counts
+        .iter()
+        .map(|slot| {
+            (0..=u8::MAX)
+                .zip(slot)
+                .filter(|&(_, &n)| n != 0)
+                .map(|(peg, &n)| (peg, n))
+                .collect()
+        })
+        .collect()
+}
 
 fn first_empty_clue(rows: &mut [ClueRow]) -> Option<&mut Option<mmsolv::Peg>> {
     rows.iter_mut()
@@ -326,7 +709,7 @@ fn first_empty_clue(rows: &mut [ClueRow]) -> Option<&mut Option<mmsolv::Peg>> {
 async fn main() {
     #![expect(clippy::too_many_lines)]
     let mut picked_peg = None;
-    let mut n_pegs_in_clues = ValLooper::new(&[3, 4, 5, 7]);
+    let mut n_pegs_in_clues = ValLooper::new(&PEG_COUNTS);
     let mut solve_msg = String::new();
     macro_rules! ptype_but_text {
         () => {
@@ -337,22 +720,46 @@ async fn main() {
     let clue_add_but = ImgButton::new(src_rects::PLUS, 110.0, 44.0, GRAY, LIGHTGRAY);
     let clue_rem_but = ImgButton::new(src_rects::MINUS, 140.0, 44.0, GRAY, LIGHTGRAY);
     let mut solve_but = SimpleButton::new("Solve".into(), 8.0, 96.0, 32);
+    let mut paste_but = SimpleButton::new("Paste".into(), 8.0, 96.0, 32);
+    let mut copy_but = SimpleButton::new("Copy".into(), 8.0, 96.0, 32);
+    let mut check_but = SimpleButton::new("Check".into(), 8.0, 96.0, 32);
+    let mut rule_options = RuleOptions::new();
+    let mut repeats_but = SimpleButton::new(rule_options.repeats_text(), 8.0, 96.0, 32);
+    let mut colors_but = SimpleButton::new(rule_options.colors_text(), 8.0, 96.0, 32);
+    let mut palette_but = SimpleButton::new(rule_options.palette_text(), 8.0, 96.0, 32);
+    let mut report = Vec::new();
+    let mut input_err = None;
     let clear_but = SimpleButton::new("X".into(), 184.0, 44.0, 40);
     let top_but = SimpleButton::new("^".into(), 184.0, 8.0, 40);
     let mut clue_rows = vec![ClueRow::new(n_pegs_in_clues.value())];
     let mut solutions = Vec::new();
+    let mut suggestion = None;
+    let mut possibilities = Vec::new();
+    // Candidates of the rows that are filled in, kept up to date as they're edited
+    let mut session = None;
+    // A solve too big for the session, run a bit each frame
+    let mut solving: Option<(Puzzle, SolveTask)> = None;
     let mut free_pegs = Vec::new();
     let mut main_y_scroll_offset = 0.0;
     let mut stored_main_y_scroll_offset = 0.0;
     let mut left_y_scroll_offset = 0.0;
     let mut stored_left_y_scroll_offset = 0.0;
+    // Forgets the results shown, once they're not about the puzzle on screen anymore
+    macro_rules! reset_results {
+        () => {
+            solutions.clear();
+            suggestion = None;
+            possibilities.clear();
+            clear_highlights(&mut clue_rows);
+        };
+    }
     macro_rules! rect_for_solve_button {
         () => {{
-            let idx = if n_pegs_in_clues.value() == 7 { 5 } else { 0 };
+            let layout = layout_for(n_pegs_in_clues.value());
             clue_rect(
                 clue_rows.len() - 1,
-                idx,
-                n_pegs_in_clues.value() == 7,
+                bottom_left_slot(layout),
+                layout,
                 main_y_scroll_offset,
             )
         }};
@@ -398,6 +805,8 @@ async fn main() {
     .unwrap();
 
     loop {
+        // What to show when a solve is done, whether it just started or took several frames
+        let mut finished = None;
         clear_background(WHITE);
         let (mx, my) = mouse_position();
 
@@ -417,7 +826,29 @@ async fn main() {
                     row.slots.resize(n_pegs_in_clues.value() as usize, None);
                 }
                 ptype_but.set_text(ptype_but_text!());
-                solutions.clear();
+                rule_options.fit_colors(n_pegs_in_clues.value());
+                colors_but.set_text(rule_options.colors_text());
+                reset_results!();
+                report.clear();
+                solve_msg.clear();
+                input_err = None;
+                clicked_something = true;
+            } else if repeats_but.mouse_over(mx, my)
+                || colors_but.mouse_over(mx, my)
+                || palette_but.mouse_over(mx, my)
+            {
+                if repeats_but.mouse_over(mx, my) {
+                    rule_options.max_repeats.go_next();
+                    repeats_but.set_text(rule_options.repeats_text());
+                } else if palette_but.mouse_over(mx, my) {
+                    rule_options.any_color = !rule_options.any_color;
+                    palette_but.set_text(rule_options.palette_text());
+                } else {
+                    rule_options.next_colors(n_pegs_in_clues.value());
+                    colors_but.set_text(rule_options.colors_text());
+                }
+                reset_results!();
+                report.clear();
                 solve_msg.clear();
                 clicked_something = true;
             } else if clue_add_but.mouse_over(mx, my) {
@@ -434,36 +865,115 @@ async fn main() {
                 row.dots = 0;
                 row.hearts = 0;
                 row.slots.iter_mut().for_each(|slot| *slot = None);
-                solutions.clear();
+                reset_results!();
+                report.clear();
                 free_pegs.clear();
                 solve_msg.clear();
+                input_err = None;
+                clicked_something = true;
+                main_y_scroll_offset = 0.0;
+            } else if paste_but.mouse_over(mx, my) {
+                let clipboard = miniquad::window::clipboard_get().unwrap_or_default();
+                match import_shortform(clipboard.trim(), &mut n_pegs_in_clues) {
+                    Ok((rows, pegs)) => {
+                        clue_rows = rows;
+                        free_pegs.clear();
+                        // Same limits as dropping them into the panel
+                        for peg in pegs {
+                            if free_pegs.len() < 6
+                                && !free_pegs.contains(&peg)
+                                && !clue_rows.iter().any(|row| row.slots.contains(&Some(peg)))
+                            {
+                                free_pegs.push(peg);
+                            }
+                        }
+                        ptype_but.set_text(ptype_but_text!());
+                        input_err = None;
+                    }
+                    Err(e) => input_err = Some(e),
+                }
+                reset_results!();
+                report.clear();
+                solve_msg.clear();
                 clicked_something = true;
                 main_y_scroll_offset = 0.0;
+            } else if copy_but.mouse_over(mx, my) {
+                match conv_mmsolv(
+                    &clue_rows,
+                    n_pegs_in_clues.value(),
+                    &free_pegs,
+                    &rule_options,
+                ) {
+                    Ok(mut puzzle) => {
+                        puzzle.clues = lettered(puzzle.clues);
+                        for peg in &mut puzzle.free_pegs {
+                            *peg = color::LETTERS[*peg as usize];
+                        }
+                        miniquad::window::clipboard_set(&mmsolv::puzzle_to_shortform(&puzzle));
+                        solve_msg = "Copied to clipboard".into();
+                    }
+                    Err(e) => solve_msg = e,
+                }
+                clicked_something = true;
+            } else if check_but.mouse_over(mx, my) {
+                let clipboard = miniquad::window::clipboard_get().unwrap_or_default();
+                match conv_mmsolv(
+                    &clue_rows,
+                    n_pegs_in_clues.value(),
+                    &free_pegs,
+                    &rule_options,
+                ) {
+                    Ok(puzzle) => match check_guess(clipboard.trim(), &puzzle) {
+                        Ok(lines) => {
+                            report = lines;
+                            input_err = None;
+                        }
+                        Err(e) => {
+                            report.clear();
+                            input_err = Some(e);
+                        }
+                    },
+                    Err(e) => solve_msg = e,
+                }
+                reset_results!();
+                clicked_something = true;
             } else if solve_but.mouse_over(mx, my) {
-                match conv_mmsolv(&clue_rows) {
-                    Ok(clues) => {
-                        solutions = solve_bruteforce_raw(&free_pegs, &clues)
-                            .take(MAX_SOLUTIONS)
-                            .collect();
-                        let len_s;
-                        solve_msg = format!(
-                            "{} solution{}",
-                            if solutions.is_empty() {
-                                "No"
+                if solving.take().is_some() {
+                    solve_msg = "Stopped solving".into();
+                } else {
+                    input_err = None;
+                    clear_highlights(&mut clue_rows);
+                    match conv_mmsolv(
+                        &clue_rows,
+                        n_pegs_in_clues.value(),
+                        &free_pegs,
+                        &rule_options,
+                    )
+                    .and_then(|puzzle| {
+                        puzzle.validate().map_err(|e| e.to_string())?;
+                        Ok(puzzle)
+                    }) {
+                        Ok(puzzle) => {
+                            sync_session(&mut session, &puzzle);
+                            if let Some(current) = &session
+                                && current.is_ready()
+                            {
+                                let first = current
+                                    .candidates()
+                                    .into_iter()
+                                    .flatten()
+                                    .take(MAX_SOLUTIONS)
+                                    .collect();
+                                let slots = current.slot_possibilities().unwrap_or_default();
+                                finished = Some(solved(&puzzle, slots, first));
                             } else {
-                                len_s = format!(
-                                    "{}{}",
-                                    solutions.len(),
-                                    if solutions.len() == 99 { "(+)" } else { "" }
-                                );
-                                &len_s
-                            },
-                            if solutions.len() > 1 { "s" } else { "" }
-                        );
-                    }
-                    Err(e) => {
-                        solve_msg = e;
-                        solutions.clear();
+                                match SolveTask::new(&puzzle) {
+                                    Ok(task) => solving = Some((puzzle, task)),
+                                    Err(e) => finished = Some(Err(e.to_string())),
+                                }
+                            }
+                        }
+                        Err(e) => finished = Some(Err(e)),
                     }
                 }
                 clicked_something = true;
@@ -498,7 +1008,7 @@ async fn main() {
                 let mut rem = None;
                 for (clue_rect, row, col) in clue_rects(
                     &clue_rows,
-                    n_pegs_in_clues.value() == 7,
+                    layout_for(n_pegs_in_clues.value()),
                     main_y_scroll_offset,
                 ) {
                     if clue_rect.contains(Vec2::new(mx, my)) {
@@ -551,10 +1061,10 @@ async fn main() {
             || is_mouse_button_pressed(MouseButton::Middle)
         {
             for peg in pickable_pegs(left_y_scroll_offset, &free_pegs) {
-                if peg.rect().contains(Vec2::new(mx, my)) {
-                    if let Some(empty) = first_empty_clue(&mut clue_rows) {
-                        *empty = Some(peg.id);
-                    }
+                if peg.rect().contains(Vec2::new(mx, my))
+                    && let Some(empty) = first_empty_clue(&mut clue_rows)
+                {
+                    *empty = Some(peg.id);
                 }
             }
         }
@@ -605,7 +1115,7 @@ async fn main() {
                 let mut ins_loc = None;
                 for (clue_rect, row, col) in clue_rects(
                     &clue_rows,
-                    n_pegs_in_clues.value() == 7,
+                    layout_for(n_pegs_in_clues.value()),
                     main_y_scroll_offset,
                 ) {
                     if clue_rect.contains(Vec2::new(mx, my)) {
@@ -622,13 +1132,82 @@ async fn main() {
                 picked_peg = None;
             }
         }
+        // The rows that are filled in, as they are now
+        let filled_in = puzzle_with_clues(
+            clue_rows.iter().filter_map(row_clue).collect(),
+            n_pegs_in_clues.value(),
+            &free_pegs,
+            &rule_options,
+        );
+        if solving
+            .as_ref()
+            .is_some_and(|(puzzle, _)| *puzzle != filled_in)
+        {
+            solving = None;
+            solve_msg.clear();
+        }
+        if let Some((_, task)) = &mut solving
+            && !task.step(SOLVE_BUDGET_PER_FRAME)
+        {
+            solve_msg = format!(
+                "Solving... {:.0}%, click Solve to stop",
+                task.progress().fraction() * 100.0
+            );
+        } else if let Some((puzzle, task)) = solving.take() {
+            let all = task.into_solutions();
+            let slots = possibilities_of(&all, puzzle.size);
+            let first = all.into_iter().take(MAX_SOLUTIONS).collect();
+            finished = Some(solved(&puzzle, slots, first));
+        }
+        if let Some(result) = finished {
+            match result {
+                Ok(result) => {
+                    let count = result.count;
+                    possibilities = result.possibilities;
+                    solutions = result.solutions;
+                    suggestion = result.suggestion;
+                    report.clear();
+                    if let Some(conflict) = &result.conflict {
+                        highlight_conflict(&mut clue_rows, conflict);
+                    }
+                    solve_msg = match count {
+                        0 if result.conflict.is_some_and(|c| !c.culprits.is_empty()) => {
+                            "No solutions, check the orange clues".into()
+                        }
+                        0 => "No solutions".into(),
+                        1 => "1 solution".into(),
+                        n if n > solutions.len() as u64 => {
+                            format!("{n} solutions (first {})", solutions.len())
+                        }
+                        n => format!("{n} solutions"),
+                    };
+                    if let Some(patterns) = result.patterns
+                        && patterns as u64 != count
+                    {
+                        solve_msg = format!("{solve_msg}, {patterns} patterns");
+                    }
+                }
+                Err(e) => {
+                    solve_msg = e;
+                    reset_results!();
+                    report.clear();
+                }
+            }
+        }
+        if sync_session(&mut session, &filled_in) {
+            solve_msg.clear();
+            clear_highlights(&mut clue_rows);
+        }
+        if let Some(current) = &mut session {
+            current.step(SOLVE_BUDGET_PER_FRAME);
+        }
         draw_clue_rows(
             &mut clue_rows,
             mx,
             my,
             picked_peg.map(|p| color::SCHEMES[p.id as usize].skin_color()),
             &tex,
-            n_pegs_in_clues.value() == 7,
+            layout_for(n_pegs_in_clues.value()),
             main_y_scroll_offset,
             &mat,
         );
@@ -640,11 +1219,38 @@ async fn main() {
             FREE_PEGS_RECT.y + FREE_PEGS_RECT.h,
             WHITE,
         );
+        let mut solutions_rect = rect_for_solve_button!();
+        if solution_count(&possibilities) > 1 {
+            solutions_rect.y += draw_possibilities(
+                &possibilities,
+                solutions_rect,
+                layout_for(n_pegs_in_clues.value()),
+            ) + 16.0;
+        }
+        if let Some(guess) = &suggestion {
+            let layout = layout_for(n_pegs_in_clues.value());
+            draw_solutions(
+                std::slice::from_ref(guess),
+                &tex,
+                solutions_rect,
+                layout,
+                &mat,
+            );
+            let (w, h) = solution_row_size(layout);
+            draw_text(
+                "< Try next",
+                solutions_rect.x + w + 8.0,
+                solutions_rect.y + 120.0 + 40.0,
+                32.0,
+                BLUE,
+            );
+            solutions_rect.y += h + 16.0;
+        }
         draw_solutions(
             &solutions,
             &tex,
-            rect_for_solve_button!(),
-            n_pegs_in_clues.value() == 7,
+            solutions_rect,
+            layout_for(n_pegs_in_clues.value()),
             &mat,
         );
         draw_free_pegs(
@@ -677,15 +1283,50 @@ async fn main() {
         clue_rem_but.draw(&tex, mx, my);
         repos_solve_but(&mut solve_but, rect_for_solve_button!());
         solve_but.draw(mx, my);
+        paste_but.rect.x = solve_but.rect.x + solve_but.rect.w + 8.0;
+        paste_but.rect.y = solve_but.rect.y;
+        paste_but.draw(mx, my);
+        copy_but.rect.x = paste_but.rect.x + paste_but.rect.w + 8.0;
+        copy_but.rect.y = paste_but.rect.y;
+        copy_but.draw(mx, my);
+        check_but.rect.x = copy_but.rect.x + copy_but.rect.w + 8.0;
+        check_but.rect.y = copy_but.rect.y;
+        check_but.draw(mx, my);
+        repeats_but.rect.x = check_but.rect.x + check_but.rect.w + 8.0;
+        repeats_but.rect.y = check_but.rect.y;
+        repeats_but.draw(mx, my);
+        colors_but.rect.x = repeats_but.rect.x + repeats_but.rect.w + 8.0;
+        colors_but.rect.y = repeats_but.rect.y;
+        colors_but.draw(mx, my);
+        palette_but.rect.x = colors_but.rect.x + colors_but.rect.w + 8.0;
+        palette_but.rect.y = colors_but.rect.y;
+        palette_but.draw(mx, my);
         top_but.draw(mx, my);
         clear_but.draw(mx, my);
+        let live_msg = match &session {
+            Some(current) if !current.is_ready() => "Counting candidates...".into(),
+            Some(current) => match current.candidate_count() {
+                Some(1) => "1 candidate".into(),
+                Some(n) => format!("{n} candidates"),
+                None => String::new(),
+            },
+            None => String::new(),
+        };
         draw_text(
-            &solve_msg,
-            solve_but.rect.x + solve_but.rect.w + 8.0,
-            solve_but.rect.y + 20.0,
+            if solve_msg.is_empty() {
+                &live_msg
+            } else {
+                &solve_msg
+            },
+            palette_but.rect.x + palette_but.rect.w + 8.0,
+            palette_but.rect.y + 20.0,
             32.,
             BLACK,
         );
+        if let Some(err) = &input_err {
+            draw_input_error(err, solve_but.rect.x, solve_but.rect.y + 64.0);
+        }
+        draw_report(&report, solve_but.rect.x, solve_but.rect.y + 64.0);
         draw_line(
             MAIN_AREA_START_X,
             0.0,
@@ -740,16 +1381,16 @@ fn draw_free_pegs(
     picked_color: Option<Color>,
     mat: &Material,
 ) {
-    if let Some(c) = picked_color {
-        if FREE_PEGS_RECT.contains(Vec2::new(mx, my)) {
-            draw_rectangle(
-                FREE_PEGS_RECT.x,
-                FREE_PEGS_RECT.y,
-                FREE_PEGS_RECT.w,
-                FREE_PEGS_RECT.h,
-                c,
-            );
-        }
+    if let Some(c) = picked_color
+        && FREE_PEGS_RECT.contains(Vec2::new(mx, my))
+    {
+        draw_rectangle(
+            FREE_PEGS_RECT.x,
+            FREE_PEGS_RECT.y,
+            FREE_PEGS_RECT.w,
+            FREE_PEGS_RECT.h,
+            c,
+        );
     }
     draw_rectangle_lines(
         FREE_PEGS_RECT.x,
@@ -765,30 +1406,66 @@ fn draw_free_pegs(
     }
 }
 
+/// Width and height of one solution drawn by `draw_solutions`
+fn solution_row_size(layout: Layout) -> (f32, f32) {
+    let rows = layout_rows(layout);
+    let padding_between_solutions = if rows > 1. { 24. } else { 0. };
+    (
+        f32::from(layout.extent().0) / 2. * 68.,
+        rows * 68. + padding_between_solutions,
+    )
+}
+
 fn draw_solutions(
     solutions: &[Vec<u8>],
     peg_tex: &Texture2D,
     bottom_rect: Rect,
-    seven_peg: bool,
+    layout: Layout,
     mat: &Material,
 ) {
+    let (_, row_height) = solution_row_size(layout);
     for (row, sol) in solutions.iter().enumerate() {
         for (col, peg_id) in sol.iter().enumerate() {
-            let x = bottom_rect.x
-                + if seven_peg {
-                    SEVEN_OFFSETS[col].0 * 68.
-                } else {
-                    col as f32 * 68.
-                };
-            let y = bottom_rect.y
-                + 120.
-                + if seven_peg {
-                    let padding_between_solutions = 24.0;
-                    row as f32 * (68. * 3. + padding_between_solutions) + SEVEN_OFFSETS[col].1 * 68.
-                } else {
-                    row as f32 * 68.
-                };
+            let (slot_x, slot_y) = slot_offset(layout, col);
+            let x = bottom_rect.x + slot_x * 68.;
+            let y = bottom_rect.y + 120. + row as f32 * row_height + slot_y * 68.;
             draw_peg(peg_tex, Pegbug { x, y, id: *peg_id }, mat);
         }
     }
 }
+
+/// Number of solutions that `possibilities` were counted from
+fn solution_count(possibilities: &[Vec<(u8, u64)>]) -> u64 {
+    possibilities
+        .first()
+        .map_or(0, |slot| slot.iter().map(|&(_, n)| n).sum())
+}
+
+/// Draws a strip for each slot, split up between the pegs it can hold in proportion to
+/// how many solutions have them there.
+///
+/// Returns the height of the drawn area.
+fn draw_possibilities(possibilities: &[Vec<(u8, u64)>], bottom_rect: Rect, layout: Layout) -> f32 {
+    const STRIP_HEIGHT: f32 = 16.0;
+    const STRIP_VERT_DISTANCE: f32 = 4.0;
+    let total = solution_count(possibilities) as f32;
+    for (col, pegs) in possibilities.iter().enumerate() {
+        let (col_x, col_y) = slot_offset(layout, col);
+        let x = bottom_rect.x + col_x * 68.;
+        let y = bottom_rect.y + 120. + col_y * (STRIP_HEIGHT + STRIP_VERT_DISTANCE);
+        let mut seg_x = x;
+        for &(peg, n) in pegs {
+            let w = PEG_SIZE * n as f32 / total;
+            draw_rectangle(
+                seg_x,
+                y,
+                w,
+                STRIP_HEIGHT,
+                color::SCHEMES[peg as usize].skin_color(),
+            );
+            seg_x += w;
+        }
+        draw_rectangle_lines(x, y, PEG_SIZE, STRIP_HEIGHT, 2.0, BLACK);
+    }
+    layout_rows(layout) * (STRIP_HEIGHT + STRIP_VERT_DISTANCE)
+}
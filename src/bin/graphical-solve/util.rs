@@ -14,6 +14,16 @@ impl<'v> ValLooper<'v> {
             self.idx = 0;
         }
     }
+    /// Jumps to `val`, if it's one of the values. Returns whether it was.
+    pub fn set(&mut self, val: u8) -> bool {
+        match self.source.iter().position(|&v| v == val) {
+            Some(idx) => {
+                self.idx = idx;
+                true
+            }
+            None => false,
+        }
+    }
     pub fn value(&self) -> u8 {
         self.source[self.idx]
     }
@@ -0,0 +1,170 @@
+use crate::{INVALID, UNSOLVABLE, invalid, options::Options};
+use std::{fmt::Write as _, io::Read, process::ExitCode, time::Instant};
+
+/// How many solutions are listed for each puzzle. The count covers all of them.
+const MAX_LISTED: usize = 100;
+
+/// The outcome for one line of input
+struct Outcome {
+    /// 1-based
+    line: usize,
+    input: String,
+    /// The first [`MAX_LISTED`] solutions, and how many there are in total
+    result: Result<(Vec<Vec<u8>>, u64), String>,
+    millis: f64,
+}
+
+/// Solves every puzzle in `path`, or stdin if there's no path or it's `-`, one per line.
+///
+/// Prints a JSON object for each, in input order. Blank lines and lines starting with `#`
+/// are skipped.
+pub fn batch(options: &Options, path: Option<&str>) -> ExitCode {
+    let mut text = String::new();
+    let read = match path {
+        None | Some("-") => std::io::stdin().lock().read_to_string(&mut text),
+        Some(path) => std::fs::File::open(path).and_then(|mut file| file.read_to_string(&mut text)),
+    };
+    if let Err(e) = read {
+        return invalid(format!("Can't read {}: {e}", path.unwrap_or("stdin")));
+    }
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    let jobs = options.jobs.unwrap_or(1);
+    let outcomes = if jobs == 1 {
+        // One at a time, printing as they're done
+        lines
+            .iter()
+            .map(|&(line, input)| {
+                let outcome = solve_line(options, line, input);
+                println!("{}", to_json(&outcome));
+                outcome
+            })
+            .collect()
+    } else {
+        let outcomes = mmsolv::par_map_ordered(&lines, jobs, |&(line, input)| {
+            solve_line(options, line, input)
+        });
+        for outcome in &outcomes {
+            println!("{}", to_json(outcome));
+        }
+        outcomes
+    };
+    if outcomes.iter().any(|outcome| outcome.result.is_err()) {
+        ExitCode::from(INVALID)
+    } else if outcomes
+        .iter()
+        .any(|outcome| matches!(outcome.result, Ok((_, 0))))
+    {
+        ExitCode::from(UNSOLVABLE)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn solve_line(options: &Options, line: usize, input: &str) -> Outcome {
+    let start = Instant::now();
+    let result = mmsolv::parse_shortform_puzzle(input)
+        .map_err(|e| e.to_string())
+        .and_then(|puzzle| options.apply(puzzle))
+        .and_then(|puzzle| {
+            let mut solutions = puzzle.solutions_backtrack().map_err(|e| e.to_string())?;
+            let listed: Vec<_> = solutions.by_ref().take(MAX_LISTED).collect();
+            let count = listed.len() as u64 + solutions.count() as u64;
+            Ok((listed, count))
+        });
+    Outcome {
+        line,
+        input: input.to_owned(),
+        result,
+        millis: start.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+/// One line of JSON, with `solutions` and `count` null if there's an error, and the
+/// other way around
+fn to_json(outcome: &Outcome) -> String {
+    let mut out = format!(
+        "{{\"line\":{},\"input\":{}",
+        outcome.line,
+        json_string(&outcome.input)
+    );
+    match &outcome.result {
+        Ok((solutions, count)) => {
+            let list: Vec<_> = solutions
+                .iter()
+                .map(|solution| json_string(&String::from_utf8_lossy(solution)))
+                .collect();
+            let _ = write!(
+                out,
+                ",\"solutions\":[{}],\"count\":{count},\"error\":null",
+                list.join(",")
+            );
+        }
+        Err(e) => {
+            let _ = write!(
+                out,
+                ",\"solutions\":null,\"count\":null,\"error\":{}",
+                json_string(e)
+            );
+        }
+    }
+    let _ = write!(out, ",\"time_ms\":{:.3}}}", outcome.millis);
+    out
+}
+
+/// `s` as a quoted JSON string
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[test]
+fn test_json_string() {
+    assert_eq!(json_string("ccprg12"), r#""ccprg12""#);
+    assert_eq!(json_string(r#"say "hi""#), r#""say \"hi\"""#);
+    assert_eq!(json_string(r"a\b"), r#""a\\b""#);
+    assert_eq!(json_string("a\nb\tc"), r#""a\nb\tc""#);
+    assert_eq!(json_string("\u{1}\u{7f}"), r#""\u0001\u007f""#);
+}
+
+#[test]
+fn test_to_json() {
+    let solved = Outcome {
+        line: 3,
+        input: "ccprg12 cyppc11".into(),
+        result: Ok((vec![b"cgrgy".to_vec(), b"cgryy".to_vec()], 7)),
+        millis: 1.5,
+    };
+    assert_eq!(
+        to_json(&solved),
+        r#"{"line":3,"input":"ccprg12 cyppc11","solutions":["cgrgy","cgryy"],"count":7,"error":null,"time_ms":1.500}"#
+    );
+    let failed = Outcome {
+        line: 1,
+        input: "ccprg1".into(),
+        result: Err("Clue 1 is \"ccprg1\"".into()),
+        millis: 0.0,
+    };
+    assert_eq!(
+        to_json(&failed),
+        r#"{"line":1,"input":"ccprg1","solutions":null,"count":null,"error":"Clue 1 is \"ccprg1\"","time_ms":0.000}"#
+    );
+}
@@ -0,0 +1,192 @@
+use crate::{UNSOLVABLE, invalid, report};
+use mmsolv::Puzzle;
+use std::process::ExitCode;
+
+/// Prints the first solution along with what else is worth knowing, or why there's none.
+///
+/// This is what happens without a subcommand.
+pub fn overview(puzzle: &Puzzle) -> ExitCode {
+    match puzzle.solve() {
+        Ok(Some(solution)) => {
+            print!("The solution is {}", String::from_utf8_lossy(&solution));
+            match mmsolv::rate_difficulty(puzzle) {
+                Ok(rating) => println!(" (difficulty {rating})"),
+                Err(_) => println!(),
+            }
+            report::print_suggestion(puzzle);
+            report::print_groups(puzzle);
+            ExitCode::SUCCESS
+        }
+        Ok(None) => {
+            println!("There is no solution. Apparently.");
+            report::print_conflict(puzzle);
+            report::print_near_solutions(puzzle);
+            ExitCode::from(UNSOLVABLE)
+        }
+        Err(e) => invalid(e),
+    }
+}
+
+/// Prints the solutions one per line, all of them or the first `limit`
+pub fn solve(puzzle: &Puzzle, limit: Option<usize>) -> ExitCode {
+    let solutions = match puzzle.solutions_backtrack() {
+        Ok(solutions) => solutions,
+        Err(e) => return invalid(e),
+    };
+    let mut found = false;
+    for solution in solutions.take(limit.unwrap_or(usize::MAX)) {
+        println!("{}", String::from_utf8_lossy(&solution));
+        found = true;
+    }
+    if found {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("There is no solution");
+        report::print_conflict(puzzle);
+        ExitCode::from(UNSOLVABLE)
+    }
+}
+
+/// Prints the number of solutions
+pub fn count(puzzle: &Puzzle) -> ExitCode {
+    match mmsolv::count_solutions(puzzle, None) {
+        Ok(count) => {
+            println!("{count}");
+            if count == 0 {
+                ExitCode::from(UNSOLVABLE)
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Err(e) => invalid(e),
+    }
+}
+
+/// How many guesses `suggest` lists
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Prints the best guesses to narrow down the solutions, best first
+pub fn suggest(puzzle: &Puzzle) -> ExitCode {
+    let scores = match mmsolv::suggest_guess(puzzle, mmsolv::SuggestStrategy::Minimax) {
+        Ok(scores) => scores,
+        Err(e) => return invalid(e),
+    };
+    if scores.is_empty() {
+        eprintln!("There is no solution");
+        return ExitCode::from(UNSOLVABLE);
+    }
+    for score in scores.iter().take(MAX_SUGGESTIONS) {
+        println!(
+            "{} ({} left at worst, {:.2} on average)",
+            String::from_utf8_lossy(&score.guess),
+            score.worst_case,
+            score.expected
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+/// Prints what the puzzle looks like to the solver, if it's valid at all
+pub fn validate(puzzle: &Puzzle) -> ExitCode {
+    println!(
+        "Valid puzzle: {} clues of {} pegs",
+        puzzle.clues.len(),
+        puzzle.size
+    );
+    println!(
+        "Pegs in play: {}",
+        String::from_utf8_lossy(&puzzle.alphabet())
+    );
+    let rules = puzzle.rules.to_string();
+    if !rules.is_empty() {
+        println!("Rules: {rules}");
+    }
+    ExitCode::SUCCESS
+}
+
+/// Prints which clues of `puzzle` are redundant, and the puzzle with as few clues as possible
+pub fn minimize(puzzle: &Puzzle) -> ExitCode {
+    let result = mmsolv::redundant_clues(puzzle)
+        .and_then(|redundant| Ok((redundant, mmsolv::minimize_clues(puzzle)?)));
+    match result {
+        Ok((redundant, minimal)) => {
+            if redundant.is_empty() {
+                println!("No clue can be removed on its own");
+            } else {
+                let list: Vec<_> = redundant
+                    .iter()
+                    .map(|&i| format!("{} ({})", i + 1, puzzle.clues[i]))
+                    .collect();
+                println!("Redundant clues: {}", list.join(", "));
+            }
+            let Some(minimal) = minimal else {
+                println!("The puzzle doesn't have exactly one solution, so it can't be minimized");
+                return ExitCode::from(UNSOLVABLE);
+            };
+            let mut smaller = puzzle.retain_clues(|i| minimal.contains(&i));
+            smaller.free_pegs = smaller.unseen_pegs();
+            println!(
+                "Smallest puzzle ({} of {} clues): {}",
+                smaller.clues.len(),
+                puzzle.clues.len(),
+                mmsolv::puzzle_to_shortform(&smaller)
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => invalid(e),
+    }
+}
+
+/// Prints a new puzzle in short form, made from `args`: size, palette, difficulty and seed.
+///
+/// Only the size and palette are required.
+pub fn generate(args: &[String]) -> ExitCode {
+    let [size, palette, rest @ ..] = args else {
+        return invalid("Usage: generate <size> <palette> [easy|medium|hard] [seed]");
+    };
+    let Ok(size) = size.parse() else {
+        return invalid(format!("Invalid size: {size}"));
+    };
+    let difficulty = match rest.first().map(String::as_str) {
+        None => mmsolv::Difficulty::default(),
+        Some("easy") => mmsolv::Difficulty::Easy,
+        Some("medium") => mmsolv::Difficulty::Medium,
+        Some("hard") => mmsolv::Difficulty::Hard,
+        Some(other) => return invalid(format!("Unknown difficulty: {other}")),
+    };
+    let seed = match rest.get(1) {
+        Some(seed) => {
+            let Ok(seed) = seed.parse() else {
+                return invalid(format!("Invalid seed: {seed}"));
+            };
+            seed
+        }
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs()),
+    };
+    match mmsolv::generate_puzzle(size, palette.as_bytes(), difficulty, seed) {
+        Ok(puzzle) => {
+            println!("{}", mmsolv::puzzle_to_shortform(&puzzle));
+            ExitCode::SUCCESS
+        }
+        Err(e) => invalid(e),
+    }
+}
+
+/// Prints why `guess` is or isn't a solution of `puzzle`
+pub fn explain(puzzle: &Puzzle, guess: &str) -> ExitCode {
+    match mmsolv::explain_guess(guess.as_bytes(), puzzle) {
+        Ok(explanation) => {
+            print!("{explanation}");
+            if explanation.ok() {
+                println!("{guess} is a solution");
+                ExitCode::SUCCESS
+            } else {
+                println!("{guess} is not a solution");
+                ExitCode::from(UNSOLVABLE)
+            }
+        }
+        Err(e) => invalid(e),
+    }
+}
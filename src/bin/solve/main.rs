@@ -0,0 +1,182 @@
+mod batch;
+mod commands;
+mod options;
+mod report;
+
+use options::Options;
+use std::{fmt::Display, process::ExitCode};
+
+/// Exit code for a puzzle without solutions, or a guess that isn't one
+const UNSOLVABLE: u8 = 1;
+/// Exit code for arguments or puzzles that don't make sense
+const INVALID: u8 = 2;
+
+const USAGE: &str = "\
+Usage: solve [options] [command] <puzzle> [args]
+
+The puzzle is in short form, like \"ccprg12 cyppc11 +bw\",
+where the letters after + are free pegs.
+
+Commands:
+  (none) [guess]   Show the first solution and more, or explain a guess
+  solve [N]        Print all solutions, or the first N
+  count            Print the number of solutions
+  explain <guess>  Tell whether a guess is a solution, and why
+  suggest          Print the best guesses to narrow down the solutions
+  validate         Check the puzzle without solving it
+  minimize         Find clues that can be left out
+  generate <size> <palette> [easy|medium|hard] [seed]
+                   Make up a new puzzle
+  batch [file]     Solve a puzzle per line of a file or stdin,
+                   printing a JSON object for each
+
+Options:";
+
+/// Prints `msg` as an error, and returns the exit code for invalid input
+fn invalid(msg: impl Display) -> ExitCode {
+    eprintln!("Error: {msg}");
+    ExitCode::from(INVALID)
+}
+
+/// What the command line asks for, with the puzzle still in short form
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    /// `--help` or `-h` anywhere
+    Help,
+    /// No arguments at all
+    Usage,
+    Overview(String),
+    Explain(String, String),
+    Solve(String, Option<usize>),
+    Count(String),
+    Suggest(String),
+    Validate(String),
+    Minimize(String),
+    Generate(Vec<String>),
+    Batch(Option<String>),
+}
+
+/// Works out the command and options from the arguments, without the program name
+fn parse_args(mut args: Vec<String>) -> Result<(Options, Command), String> {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok((Options::default(), Command::Help));
+    }
+    let options = Options::take(&mut args)?;
+    let Some((first, rest)) = args.split_first() else {
+        return Ok((options, Command::Usage));
+    };
+    if options.jobs.is_some() && first != "batch" {
+        return Err("--jobs only works with batch".into());
+    }
+    let (command, rest) = match first.as_str() {
+        "generate" => return Ok((options, Command::Generate(rest.to_vec()))),
+        command @ ("solve" | "count" | "explain" | "suggest" | "validate" | "minimize"
+        | "batch") => (command, rest),
+        _ => ("", &args[..]),
+    };
+    let command = match (command, rest) {
+        ("batch", []) => Command::Batch(None),
+        ("batch", [path]) => Command::Batch(Some(path.clone())),
+        (command, []) => return Err(format!("{command} needs a puzzle")),
+        ("", [puzzle]) => Command::Overview(puzzle.clone()),
+        ("" | "explain", [puzzle, guess]) => Command::Explain(puzzle.clone(), guess.clone()),
+        ("solve", [puzzle]) => Command::Solve(puzzle.clone(), None),
+        ("solve", [puzzle, limit]) => match limit.parse() {
+            Ok(limit) => Command::Solve(puzzle.clone(), Some(limit)),
+            Err(_) => return Err(format!("Invalid number of solutions: {limit}")),
+        },
+        ("count", [puzzle]) => Command::Count(puzzle.clone()),
+        ("suggest", [puzzle]) => Command::Suggest(puzzle.clone()),
+        ("validate", [puzzle]) => Command::Validate(puzzle.clone()),
+        ("minimize", [puzzle]) => Command::Minimize(puzzle.clone()),
+        _ => {
+            return Err(format!(
+                "Wrong number of arguments\n\n{USAGE}\n{}",
+                options::HELP
+            ));
+        }
+    };
+    Ok((options, command))
+}
+
+fn main() -> ExitCode {
+    let (options, command) = match parse_args(std::env::args().skip(1).collect()) {
+        Ok(parsed) => parsed,
+        Err(e) => return invalid(e),
+    };
+    let puzzle = |arg: &str| options.puzzle(arg);
+    let result = match &command {
+        Command::Help => {
+            println!("{USAGE}\n{}", options::HELP);
+            return ExitCode::SUCCESS;
+        }
+        Command::Usage => {
+            eprintln!("{USAGE}\n{}", options::HELP);
+            return ExitCode::from(INVALID);
+        }
+        Command::Generate(args) => return commands::generate(args),
+        Command::Batch(path) => return batch::batch(&options, path.as_deref()),
+        Command::Overview(arg) => puzzle(arg).map(|puzzle| commands::overview(&puzzle)),
+        Command::Explain(arg, guess) => puzzle(arg).map(|puzzle| commands::explain(&puzzle, guess)),
+        Command::Solve(arg, limit) => puzzle(arg).map(|puzzle| commands::solve(&puzzle, *limit)),
+        Command::Count(arg) => puzzle(arg).map(|puzzle| commands::count(&puzzle)),
+        Command::Suggest(arg) => puzzle(arg).map(|puzzle| commands::suggest(&puzzle)),
+        Command::Validate(arg) => puzzle(arg).map(|puzzle| commands::validate(&puzzle)),
+        Command::Minimize(arg) => puzzle(arg).map(|puzzle| commands::minimize(&puzzle)),
+    };
+    result.unwrap_or_else(invalid)
+}
+
+#[test]
+fn test_parse_args() {
+    let args = |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+    let command = |line: &str| parse_args(args(line)).map(|(_, command)| command);
+    assert_eq!(command(""), Ok(Command::Usage));
+    assert_eq!(command("solve ccprg12 -h"), Ok(Command::Help));
+    assert_eq!(command("ccprg12"), Ok(Command::Overview("ccprg12".into())));
+    assert_eq!(
+        command("ccprg12 cgrgy"),
+        Ok(Command::Explain("ccprg12".into(), "cgrgy".into()))
+    );
+    assert_eq!(
+        command("explain ccprg12 cgrgy"),
+        Ok(Command::Explain("ccprg12".into(), "cgrgy".into()))
+    );
+    assert_eq!(
+        command("solve ccprg12 3"),
+        Ok(Command::Solve("ccprg12".into(), Some(3)))
+    );
+    assert_eq!(
+        command("solve ccprg12 x"),
+        Err("Invalid number of solutions: x".into())
+    );
+    assert_eq!(command("count"), Err("count needs a puzzle".into()));
+    assert!(
+        command("count ccprg12 1")
+            .unwrap_err()
+            .starts_with("Wrong number of arguments")
+    );
+    assert_eq!(
+        command("generate 5 rgby hard"),
+        Ok(Command::Generate(args("5 rgby hard")))
+    );
+    assert_eq!(command("batch"), Ok(Command::Batch(None)));
+    assert_eq!(
+        command("--jobs 4 batch puzzles.txt"),
+        Ok(Command::Batch(Some("puzzles.txt".into())))
+    );
+    assert!(
+        command("batch a b")
+            .unwrap_err()
+            .starts_with("Wrong number of arguments")
+    );
+    assert_eq!(
+        command("--jobs 4 ccprg12"),
+        Err("--jobs only works with batch".into())
+    );
+    let (options, command) =
+        parse_args(args("--size 7 validate --rules no-repeats ccprg12")).unwrap();
+    assert_eq!(command, Command::Validate("ccprg12".into()));
+    assert_eq!(options.size, Some(7));
+    assert_eq!(options.rules.as_deref(), Some("no-repeats"));
+}
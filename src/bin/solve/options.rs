@@ -0,0 +1,88 @@
+use mmsolv::Puzzle;
+
+/// Flags that change the puzzle, which can go anywhere on the command line
+#[derive(Default)]
+pub struct Options {
+    /// Options for [`mmsolv::Rules::with_options`], on top of the game's rules
+    pub rules: Option<String>,
+    /// Letters of pegs that may be in the solution without being in a clue
+    pub free_pegs: Option<String>,
+    /// Number of pegs in the solution, if not taken from the first clue
+    pub size: Option<usize>,
+    /// Letters of all pegs in play
+    pub palette: Option<String>,
+    /// How many puzzles to solve at once in batch mode
+    pub jobs: Option<usize>,
+}
+
+/// What the flags do, for the usage text
+pub const HELP: &str = "\
+\x20 --free-pegs LETTERS  Pegs that may be in the solution without being in a clue,
+                       like a +LETTERS suffix on the puzzle. --free for short.
+  --palette LETTERS    All pegs in play, seen in clues or not
+  --size N             Pegs in the solution, instead of taking it from the first clue
+  --rules OPTIONS      Extra rules, like no-repeats,colors=4
+  --jobs N             Puzzles to solve at once in batch mode";
+
+impl Options {
+    /// Takes the flags out of `args`, leaving only the positional arguments
+    pub fn take(args: &mut Vec<String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(pos) = args.iter().position(|arg| arg.starts_with("--")) {
+            let name = args.remove(pos);
+            if pos == args.len() {
+                return Err(format!("{name} needs a value"));
+            }
+            let value = args.remove(pos);
+            match name.as_str() {
+                "--rules" => options.rules = Some(value),
+                "--free-pegs" | "--free" => options.free_pegs = Some(value),
+                "--palette" => options.palette = Some(value),
+                "--size" => {
+                    let size = value
+                        .parse()
+                        .map_err(|_| format!("Invalid size: {value}"))?;
+                    options.size = Some(size);
+                }
+                "--jobs" => {
+                    let jobs = value
+                        .parse()
+                        .ok()
+                        .filter(|&jobs| jobs != 0)
+                        .ok_or_else(|| format!("Invalid number of jobs: {value}"))?;
+                    options.jobs = Some(jobs);
+                }
+                _ => return Err(format!("Unknown option: {name}")),
+            }
+        }
+        Ok(options)
+    }
+    /// Parses the short form `arg` into a puzzle with these options.
+    ///
+    /// The error points out where it went wrong.
+    pub fn puzzle(&self, arg: &str) -> Result<Puzzle, String> {
+        let puzzle = mmsolv::parse_shortform_puzzle(arg)
+            .map_err(|e| format!("{e}\n{arg}\n{:>1$}", "^", e.offset + 1))?;
+        self.apply(puzzle)
+    }
+
+    /// Changes `puzzle` as these options say, and checks that it can be solved
+    pub fn apply(&self, mut puzzle: Puzzle) -> Result<Puzzle, String> {
+        if let Some(size) = self.size {
+            let free_pegs = puzzle.free_pegs;
+            puzzle = Puzzle::new(size, puzzle.clues);
+            puzzle.free_pegs = free_pegs;
+        }
+        if let Some(spec) = &self.rules {
+            puzzle.rules = puzzle.rules.with_options(spec).map_err(|e| e.to_string())?;
+        }
+        if let Some(free_pegs) = &self.free_pegs {
+            puzzle.free_pegs.extend(free_pegs.bytes());
+        }
+        if let Some(palette) = &self.palette {
+            puzzle.palette = Some(palette.bytes().collect());
+        }
+        puzzle.validate().map_err(|e| e.to_string())?;
+        Ok(puzzle)
+    }
+}
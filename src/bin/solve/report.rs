@@ -0,0 +1,123 @@
+/// Most solutions to suggest a guess for. Counting and scoring more takes too long.
+const MAX_SOLUTIONS_TO_SUGGEST: u64 = 1000;
+
+/// If `puzzle` has more than one solution, but not too many, prints the best guess to
+/// narrow them down
+pub fn print_suggestion(puzzle: &mmsolv::Puzzle) {
+    let Ok(count) = mmsolv::count_solutions(puzzle, Some(MAX_SOLUTIONS_TO_SUGGEST + 1)) else {
+        return;
+    };
+    if count < 2 {
+        return;
+    }
+    if count > MAX_SOLUTIONS_TO_SUGGEST {
+        println!("It's one of more than {MAX_SOLUTIONS_TO_SUGGEST} possible solutions.");
+        return;
+    }
+    println!("It's one of {count} possible solutions.");
+    if let Ok(scores) = mmsolv::suggest_guess(puzzle, mmsolv::SuggestStrategy::Minimax)
+        && let Some(best) = scores.first()
+    {
+        println!(
+            "Suggested next guess: {} ({} left at worst, {:.2} on average)",
+            String::from_utf8_lossy(&best.guess),
+            best.worst_case,
+            best.expected
+        );
+    }
+}
+
+/// How many groups of solutions to list
+const MAX_GROUPS: usize = 10;
+
+/// If `puzzle` has pegs no clue has, prints its solutions grouped by where those pegs go
+pub fn print_groups(puzzle: &mmsolv::Puzzle) {
+    let unseen = puzzle.unseen_pegs();
+    if unseen.is_empty() {
+        return;
+    }
+    let Ok(groups) = mmsolv::group_solutions(puzzle) else {
+        return;
+    };
+    if groups
+        .iter()
+        .map(|group| group.solutions.len())
+        .sum::<usize>()
+        < 2
+    {
+        return;
+    }
+    println!(
+        "Solutions by pattern, where 1, 2, ... are different pegs out of {}:",
+        String::from_utf8_lossy(&unseen)
+    );
+    for group in groups.iter().take(MAX_GROUPS) {
+        match group.solutions.len() {
+            1 => println!("  {group}"),
+            n => println!("  {group} ({n} solutions)"),
+        }
+    }
+    if groups.len() > MAX_GROUPS {
+        println!("  ...and {} more", groups.len() - MAX_GROUPS);
+    }
+}
+
+/// Prints which clues of the unsolvable `puzzle` contradict each other
+pub fn print_conflict(puzzle: &mmsolv::Puzzle) {
+    let Ok(Some(conflict)) = mmsolv::find_conflict(puzzle) else {
+        return;
+    };
+    let clue_list = |indices: &[usize]| {
+        indices
+            .iter()
+            .map(|&i| format!("{} ({})", i + 1, puzzle.clues[i]))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    println!(
+        "These clues contradict each other: {}",
+        clue_list(&conflict.minimal_subset)
+    );
+    if !conflict.culprits.is_empty() {
+        println!(
+            "Removing any one of these clues makes it solvable: {}",
+            clue_list(&conflict.culprits)
+        );
+    }
+}
+
+/// How many near-solutions to list for an unsolvable puzzle
+const MAX_NEAR_SOLUTIONS: usize = 5;
+
+/// Prints the guesses that would solve `puzzle` if one of its clues was misread
+pub fn print_near_solutions(puzzle: &mmsolv::Puzzle) {
+    let Ok(near) = mmsolv::solve_tolerant(puzzle, 1) else {
+        return;
+    };
+    if near.is_empty() {
+        return;
+    }
+    println!("Closest solutions if one clue is wrong:");
+    for near in near.iter().take(MAX_NEAR_SOLUTIONS) {
+        let mismatches: Vec<_> = near
+            .mismatches
+            .iter()
+            .map(|m| {
+                format!(
+                    "clue {} ({}) should be {}",
+                    m.clue + 1,
+                    puzzle.clues[m.clue],
+                    m.actual
+                )
+            })
+            .collect();
+        println!(
+            "  {}: {}",
+            String::from_utf8_lossy(&near.guess),
+            mismatches.join(", ")
+        );
+    }
+    if near.len() > MAX_NEAR_SOLUTIONS {
+        println!("  ...and {} more", near.len() - MAX_NEAR_SOLUTIONS);
+    }
+}
@@ -2,44 +2,109 @@
 //! combinations of pegs, and checks each against all clues. If a generated guess doesn't contradict
 //! any clue, it is a valid solution.
 
-use crate::{Clue, Indicator, Marker, Peg, Pegs, combinations::SliceCombo};
-use std::{collections::HashSet, convert::TryInto};
+use crate::{
+    Clue, Indicator, Marker, Peg, Pegs, Rules, SolveError, check_clues, combinations::SliceCombo,
+};
+use std::{borrow::Borrow, collections::BTreeSet};
 
-#[must_use]
-pub fn solve_bruteforce(free_pegs: &Pegs, clues: &[Clue]) -> Option<String> {
-    let mut raw = solve_bruteforce_raw(free_pegs, clues);
-    raw.next().and_then(|guess| String::from_utf8(guess).ok())
+/// # Errors
+///
+/// If `clues` don't make up a valid puzzle
+pub fn solve_bruteforce(free_pegs: &Pegs, clues: &[Clue]) -> Result<Option<String>, SolveError> {
+    let mut raw = solve_bruteforce_raw(free_pegs, clues)?;
+    Ok(raw.next().and_then(|guess| String::from_utf8(guess).ok()))
 }
 
-/// # Panics
+/// Solves `clues` with the game's rules for their size.
+///
+/// Solutions come in ascending lexicographic order of their peg values.
+/// See [`Puzzle`](crate::Puzzle) for more control.
+///
+/// # Errors
 ///
-/// If `clues` is empty
+/// If `clues` don't make up a valid puzzle
 pub fn solve_bruteforce_raw<'a>(
     free_pegs: &'a Pegs,
     clues: &'a [Clue],
-) -> impl Iterator<Item = Vec<u8>> + 'a {
-    let Some(first_clue) = clues.first() else {
-        panic!("Can't solve without clues");
-    };
-    let set: HashSet<Peg> = clues
+) -> Result<impl Iterator<Item = Vec<u8>> + 'a, SolveError> {
+    let size = clues.first().map_or(0, |clue| clue.pegs.len());
+    check_clues(size, clues)?;
+    solutions(free_pegs, clues, size, Rules::for_size(size))
+}
+
+pub(crate) fn solutions<'a>(
+    free_pegs: &Pegs,
+    clues: &'a [Clue],
+    size: usize,
+    rules: Rules,
+) -> Result<Filtered<'a, Vec<Peg>>, SolveError> {
+    Ok(Filtered {
+        combos: SliceCombo::new(alphabet(free_pegs, clues), size)
+            .ok_or(SolveError::TooManyCandidates)?,
+        clues,
+        rules,
+    })
+}
+
+/// The combinations of `combos` that are solutions.
+///
+/// Only the solutions get allocated.
+pub(crate) struct Filtered<'a, S> {
+    pub(crate) combos: SliceCombo<Peg, S>,
+    pub(crate) clues: &'a [Clue],
+    pub(crate) rules: Rules,
+}
+
+impl<S: Borrow<[Peg]>> Iterator for Filtered<'_, S> {
+    type Item = Vec<Peg>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(guess) = self.combos.next_ref() {
+            if validate_guess(guess, self.clues, self.rules) {
+                return Some(guess.to_vec());
+            }
+        }
+        None
+    }
+}
+
+/// All the different pegs that can appear in a solution, in ascending order.
+///
+/// This order is what makes the solution order deterministic.
+pub(crate) fn alphabet(free_pegs: &Pegs, clues: &[Clue]) -> Vec<Peg> {
+    let set: BTreeSet<Peg> = clues
         .iter()
         .flat_map(|clue| clue.pegs.iter().copied())
         .chain(free_pegs.iter().copied())
         .collect();
-    let set: Vec<Peg> = set.into_iter().collect();
-    let combos = SliceCombo::new(set, first_clue.pegs.len());
-    combos.filter(move |guess| validate_guess(guess, clues))
+    set.into_iter().collect()
 }
 
 /// Compares `guess` against `clue`, and returns the resulting indicator
-fn compare(guess: &Pegs, clue: &Pegs) -> Indicator {
-    assert!(guess.len() == clue.len());
+pub(crate) fn compare(guess: &Pegs, clue: &Pegs) -> Indicator {
+    let mut dots: u8 = 0;
+    let mut hearts: u8 = 0;
+    match_pegs(guess, clue, |_| hearts += 1, |_, _| dots += 1);
+    Indicator { dots, hearts }
+}
+
+/// Pairs up the pegs of `guess` and `clue`, the way the game counts them.
+///
+/// Calls `heart` with every slot where both have the same peg, and then `dot` with
+/// `(guess slot, clue slot)` for every other guess peg that matches a clue peg not used yet.
+pub(crate) fn match_pegs(
+    guess: &Pegs,
+    clue: &Pegs,
+    mut heart: impl FnMut(usize),
+    mut dot: impl FnMut(usize, usize),
+) {
+    debug_assert!(guess.len() == clue.len());
     let len = guess.len();
     let mut markers = vec![Marker::None; len];
     // First determine hearts
     for (i, &g_peg) in guess.iter().enumerate() {
         if clue[i] == g_peg {
             markers[i] = Marker::Heart;
+            heart(i);
         }
     }
     // Then determine dots
@@ -51,53 +116,22 @@ fn compare(guess: &Pegs, clue: &Pegs) -> Indicator {
         for (j, &c_peg) in clue.iter().enumerate() {
             if g_peg == c_peg && markers[j] == Marker::None {
                 markers[j] = Marker::Dot;
+                dot(i, j);
                 // A single guess peg can only ever count as one dot, and
                 // we just counted a dot, so break.
                 break;
             }
         }
     }
-    let mut dots: u8 = 0;
-    let mut hearts: u8 = 0;
-    for marker in markers {
-        if marker == Marker::Dot {
-            dots += 1;
-        } else if marker == Marker::Heart {
-            hearts += 1;
-        }
-    }
-    Indicator { dots, hearts }
-}
-
-fn seven_peg_any_neighbouring_same([p0, p1, p2, p3, p4, p5, p6]: [Peg; 7]) -> bool {
-    //  [0][1]
-    // [2][3][4]
-    //  [5][6]
-    // Sorry...
-    p0 == p1
-        || p0 == p2
-        || p0 == p3
-        || p1 == p3
-        || p1 == p4
-        || p2 == p3
-        || p2 == p5
-        || p3 == p4
-        || p3 == p5
-        || p3 == p6
-        || p4 == p6
-        || p5 == p6
 }
 
-fn validate_guess(guess: &Pegs, clues: &[Clue]) -> bool {
-    for clue in clues {
-        if clue.indicator != compare(guess, &clue.pegs) {
-            return false;
-        }
-        if guess.len() == 7 && seven_peg_any_neighbouring_same(guess.try_into().unwrap()) {
-            return false;
-        }
+pub(crate) fn validate_guess(guess: &Pegs, clues: &[Clue], rules: Rules) -> bool {
+    if rules.check(guess).is_some() {
+        return false;
     }
-    true
+    clues
+        .iter()
+        .all(|clue| clue.indicator == compare(guess, &clue.pegs))
 }
 
 #[test]
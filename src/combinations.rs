@@ -1,22 +1,129 @@
 //! A modified version of the mmpw type. Doesn't use const generics.
 //! Apparently permutations is the wrong term.
+//!
+//! Every combination has an index: the slot indices read as a number in base `slice.len()`,
+//! with the first slot as the most significant digit. Combinations are produced in index order,
+//! and a `SliceCombo` can be limited to a range of indices, which allows splitting the work.
+//!
+//! [`SliceCombo::next_ref`] lends out a reused buffer instead of allocating a new `Vec`
+//! for each combination, and [`Iterator::nth`] jumps straight to the wanted index.
 
-use std::{borrow::Borrow, marker::PhantomData};
+use std::{borrow::Borrow, marker::PhantomData, ops::Range};
 
+/// Every way of filling `slots` slots with items from `slice`, repetitions allowed
+#[derive(Clone)]
 pub struct SliceCombo<T, S> {
     slice: S,
     indices: Box<[usize]>,
-    first: bool,
+    /// Index of the next combination. Unlike `indices`, it doesn't wrap around at the end.
+    position: usize,
+    /// How many combinations are left, starting at `indices`
+    remaining: usize,
+    /// Reused by `next_ref`
+    buf: Vec<T>,
     _p: PhantomData<T>,
 }
 
-impl<T, S> SliceCombo<T, S> {
-    pub fn new(slice: S, slots: usize) -> Self {
-        Self {
+/// Number of combinations of `slots` slots with `len` possible values each.
+///
+/// `None` if there are more than fit in a `usize`.
+pub fn total(len: usize, slots: usize) -> Option<usize> {
+    u32::try_from(slots)
+        .ok()
+        .and_then(|slots| len.checked_pow(slots))
+}
+
+impl<T, S: Borrow<[T]>> SliceCombo<T, S> {
+    /// All combinations, or `None` if there are too many to index with a `usize`
+    pub fn new(slice: S, slots: usize) -> Option<Self> {
+        let end = total(slice.borrow().len(), slots)?;
+        Some(Self::with_range(slice, slots, 0..end))
+    }
+    /// Only the combinations with an index in `range`
+    pub fn with_range(slice: S, slots: usize, range: Range<usize>) -> Self {
+        let end =
+            total(slice.borrow().len(), slots).map_or(range.end, |total| range.end.min(total));
+        let range = range.start..end;
+        let mut neu = Self {
             slice,
             indices: vec![0; slots].into_boxed_slice(),
-            first: true,
+            position: range.start,
+            remaining: range.len(),
+            buf: Vec::with_capacity(slots),
             _p: PhantomData,
+        };
+        neu.seek(range.start);
+        neu
+    }
+    /// Splits the remaining combinations into at most `parts` disjoint, contiguous ranges,
+    /// in enumeration order
+    pub fn split(self, parts: usize) -> Vec<Self>
+    where
+        S: Clone,
+    {
+        let start = self.index();
+        let parts = parts.clamp(1, self.remaining.max(1));
+        let chunk = self.remaining.div_ceil(parts);
+        let slots = self.indices.len();
+        (0..parts)
+            .map(|i| {
+                let begin = start + (i * chunk).min(self.remaining);
+                let end = start + ((i + 1) * chunk).min(self.remaining);
+                Self::with_range(self.slice.clone(), slots, begin..end)
+            })
+            .collect()
+    }
+    /// Index of the next combination, or the end of the range once there are none left
+    pub fn index(&self) -> usize {
+        self.position
+    }
+    /// Like [`Iterator::next`], but returns a buffer that is reused by the next call
+    pub fn next_ref(&mut self) -> Option<&[T]>
+    where
+        T: Clone,
+    {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.position += 1;
+        let slice = self.slice.borrow();
+        self.buf.clear();
+        self.buf
+            .extend(self.indices.iter().map(|&idx| slice[idx].clone()));
+        for idx in self.indices.iter_mut().rev() {
+            if *idx < slice.len() - 1 {
+                *idx += 1;
+                break;
+            }
+            *idx = 0;
+        }
+        Some(&self.buf)
+    }
+    /// Like [`Iterator::nth`], but returns a buffer that is reused by the next call
+    pub fn nth_ref(&mut self, n: usize) -> Option<&[T]>
+    where
+        T: Clone,
+    {
+        if n >= self.remaining {
+            self.position += self.remaining;
+            self.remaining = 0;
+            return None;
+        }
+        self.position += n;
+        self.seek(self.position);
+        self.remaining -= n;
+        self.next_ref()
+    }
+    /// Points `indices` at the combination with index `index`
+    fn seek(&mut self, mut index: usize) {
+        let len = self.slice.borrow().len();
+        if len == 0 {
+            return;
+        }
+        for idx in self.indices.iter_mut().rev() {
+            *idx = index % len;
+            index /= len;
         }
     }
 }
@@ -24,30 +131,79 @@ impl<T, S> SliceCombo<T, S> {
 impl<T: Clone, S: Borrow<[T]>> Iterator for SliceCombo<T, S> {
     type Item = Vec<T>;
     fn next(&mut self) -> Option<Self::Item> {
-        let slice = self.slice.borrow();
-        if slice.is_empty() {
-            return None;
-        }
-        let len = self.indices.len();
-        let mut arr = vec![slice[0].clone(); len];
-        if self.first {
-            self.first = false;
-            return Some(arr);
-        }
-        let mut i = len - 1;
-        loop {
-            if self.indices[i] < slice.len() - 1 {
-                self.indices[i] += 1;
-                for (j, &indice) in self.indices.iter().enumerate() {
-                    arr[j] = slice[indice].clone();
-                }
-                return Some(arr);
-            }
-            self.indices[i] = 0;
-            if i == 0 {
-                return None;
-            }
-            i -= 1;
-        }
+        self.next_ref().map(<[T]>::to_vec)
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.nth_ref(n).map(<[T]>::to_vec)
+    }
+}
+
+impl<T: Clone, S: Borrow<[T]>> ExactSizeIterator for SliceCombo<T, S> {}
+
+#[test]
+fn test_nth() {
+    let all: Vec<_> = SliceCombo::new(&b"abcd"[..], 4).unwrap().collect();
+    let mut combos = SliceCombo::new(&b"abcd"[..], 4).unwrap();
+    assert_eq!(combos.len(), 256);
+    assert_eq!(combos.nth(100).as_ref(), Some(&all[100]));
+    assert_eq!(combos.len(), 155);
+    assert_eq!(combos.next_ref(), Some(&all[101][..]));
+    assert_eq!(combos.nth(153).as_ref(), Some(&all[255]));
+    assert_eq!(combos.next(), None);
+    let mut combos = SliceCombo::new(&b"abcd"[..], 4).unwrap();
+    assert_eq!(combos.nth(256), None);
+    assert_eq!(combos.len(), 0);
+}
+
+#[test]
+fn test_split() {
+    let all: Vec<_> = SliceCombo::new(&b"abc"[..], 3).unwrap().collect();
+    assert_eq!(all.len(), 27);
+    assert_eq!(all[0], b"aaa");
+    assert_eq!(all[5], b"abc");
+    for parts in [1, 2, 5, 27, 100] {
+        let split: Vec<_> = SliceCombo::new(&b"abc"[..], 3)
+            .unwrap()
+            .split(parts)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(split, all, "{parts}");
+    }
+}
+
+#[test]
+fn test_index() {
+    let all: Vec<_> = SliceCombo::new(&b"abc"[..], 2).unwrap().collect();
+    let mut combos = SliceCombo::with_range(&b"abc"[..], 2, 4..7);
+    assert_eq!(combos.index(), 4);
+    combos.nth(1);
+    assert_eq!(combos.index(), 6);
+    combos.next();
+    // Exhausted: the index stays at the end of the range instead of wrapping to 0
+    assert_eq!(combos.next(), None);
+    assert_eq!(combos.index(), 7);
+    let mut rest = SliceCombo::new(&b"abc"[..], 2).unwrap();
+    rest.nth(combos.index() - 1);
+    assert_eq!(rest.collect::<Vec<_>>(), all[7..]);
+    // A range that hasn't started yet
+    let split = SliceCombo::new(&b"abc"[..], 2).unwrap().split(3);
+    assert_eq!(
+        split.iter().map(SliceCombo::index).collect::<Vec<_>>(),
+        [0, 3, 6]
+    );
+    let mut last = SliceCombo::new(&b"abc"[..], 2).unwrap();
+    assert_eq!(last.nth(9), None);
+    assert_eq!(last.index(), 9);
+}
+
+#[test]
+fn test_total() {
+    assert_eq!(total(8, 7), Some(8usize.pow(7)));
+    assert_eq!(total(0, 0), Some(1));
+    assert_eq!(total(usize::MAX, 2), None);
+    assert!(SliceCombo::new(vec![0u8; 1 << 16], 5).is_none());
 }
@@ -0,0 +1,71 @@
+//! Finds out which clues are to blame when a puzzle has no solution.
+//!
+//! That nearly always means a clue was entered wrong, and the clues that contradict each other
+//! narrow down which one.
+
+use crate::{Puzzle, SolveError, backtrack::Backtrack};
+
+/// Clues that contradict each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Indices of a set of clues that can't all be satisfied at once, but any smaller
+    /// part of which can be
+    pub minimal_subset: Vec<usize>,
+    /// Indices of the clues whose removal alone makes the puzzle solvable
+    pub culprits: Vec<usize>,
+}
+
+/// Explains why `puzzle` has no solution, or returns `None` if it has one.
+///
+/// Takes a handful of solver runs, one for each clue and one for each clue in the
+/// minimal subset.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn find_conflict(puzzle: &Puzzle) -> Result<Option<Conflict>, SolveError> {
+    puzzle.validate()?;
+    let solvable = |keep: &[bool]| {
+        let subset = puzzle.retain_clues(|i| keep[i]);
+        Backtrack::for_puzzle(&subset).advance()
+    };
+    let mut keep = vec![true; puzzle.clues.len()];
+    if solvable(&keep) {
+        return Ok(None);
+    }
+    // Drop every clue that isn't needed for the contradiction
+    for i in 0..keep.len() {
+        keep[i] = false;
+        if solvable(&keep) {
+            keep[i] = true;
+        }
+    }
+    let minimal_subset: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+    // A culprit must be part of every contradiction, so it's in this one too
+    let culprits = minimal_subset
+        .iter()
+        .copied()
+        .filter(|&culprit| solvable(&(0..keep.len()).map(|i| i != culprit).collect::<Vec<_>>()))
+        .collect();
+    Ok(Some(Conflict {
+        minimal_subset,
+        culprits,
+    }))
+}
+
+#[test]
+fn test_conflict() {
+    use crate::parse_shortform;
+    let solvable = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03"));
+    assert_eq!(find_conflict(&solvable), Ok(None));
+    // The last clue mistyped, from yrccc03
+    let mistyped = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc30"));
+    let conflict = find_conflict(&mistyped).unwrap().unwrap();
+    assert!(conflict.culprits.contains(&4));
+    assert!(conflict.minimal_subset.contains(&4));
+    // Every clue in the minimal subset is needed for the contradiction
+    for &i in &conflict.minimal_subset {
+        let subset = mistyped.retain_clues(|j| j != i && conflict.minimal_subset.contains(&j));
+        assert!(Backtrack::for_puzzle(&subset).advance());
+    }
+}
@@ -0,0 +1,163 @@
+//! Explains why a guess is or isn't a solution, clue by clue.
+
+use crate::{
+    Indicator, Pegs, Puzzle, RuleViolation, SolveError,
+    bruteforce::{compare, match_pegs},
+};
+use std::fmt;
+
+/// How a guess fares against one clue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClueCheck {
+    /// Index of the clue
+    pub clue: usize,
+    /// The indicator the clue has
+    pub expected: Indicator,
+    /// The indicator the clue would have if the guess was the solution
+    pub actual: Indicator,
+    /// Slots where the guess and the clue have the same peg
+    pub hearts: Vec<usize>,
+    /// `(guess slot, clue slot)` pairs with the same peg in different places
+    pub dots: Vec<(usize, usize)>,
+}
+
+impl ClueCheck {
+    /// Whether the guess agrees with the clue
+    #[must_use]
+    pub fn ok(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// Why a guess is or isn't a solution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// One check for every clue, in order
+    pub clues: Vec<ClueCheck>,
+    /// The first rule the guess breaks, if any
+    pub broken_rule: Option<RuleViolation>,
+}
+
+impl Explanation {
+    /// Whether the guess is a solution
+    #[must_use]
+    pub fn ok(&self) -> bool {
+        self.broken_rule.is_none() && self.clues.iter().all(ClueCheck::ok)
+    }
+}
+
+/// Checks `guess` against every clue and rule of `puzzle`
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate), or `guess` is the wrong size
+pub fn explain_guess(guess: &Pegs, puzzle: &Puzzle) -> Result<Explanation, SolveError> {
+    puzzle.validate()?;
+    if guess.len() != puzzle.size {
+        return Err(SolveError::GuessLength {
+            expected: puzzle.size,
+            found: guess.len(),
+        });
+    }
+    let clues = puzzle
+        .clues
+        .iter()
+        .enumerate()
+        .map(|(i, clue)| {
+            let mut hearts = Vec::new();
+            let mut dots = Vec::new();
+            match_pegs(
+                guess,
+                &clue.pegs,
+                |i| hearts.push(i),
+                |i, j| dots.push((i, j)),
+            );
+            ClueCheck {
+                clue: i,
+                expected: clue.indicator,
+                actual: compare(guess, &clue.pegs),
+                hearts,
+                dots,
+            }
+        })
+        .collect();
+    Ok(Explanation {
+        clues,
+        broken_rule: puzzle.rules.check(guess),
+    })
+}
+
+/// Lists slots as 1-based numbers
+fn slot_list(slots: impl Iterator<Item = String>) -> String {
+    let list: Vec<String> = slots.collect();
+    if list.is_empty() {
+        "none".into()
+    } else {
+        list.join(", ")
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for check in &self.clues {
+            writeln!(
+                f,
+                "{} clue {}: wants {} hearts {} dots, gets {} hearts {} dots",
+                if check.ok() { "ok  " } else { "FAIL" },
+                check.clue + 1,
+                check.expected.hearts,
+                check.expected.dots,
+                check.actual.hearts,
+                check.actual.dots,
+            )?;
+            writeln!(
+                f,
+                "     hearts at slots: {}; dots from slots: {}",
+                slot_list(check.hearts.iter().map(|i| (i + 1).to_string())),
+                slot_list(
+                    check
+                        .dots
+                        .iter()
+                        .map(|(g, c)| format!("{}->{}", g + 1, c + 1))
+                ),
+            )?;
+        }
+        if let Some(violation) = self.broken_rule {
+            writeln!(f, "FAIL {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_explain() {
+    use crate::parse_shortform;
+    let puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03"));
+    assert!(explain_guess(b"cgrgy", &puzzle).unwrap().ok());
+    let explanation = explain_guess(b"cgrgr", &puzzle).unwrap();
+    assert!(!explanation.ok());
+    for check in &explanation.clues {
+        assert_eq!(usize::from(check.actual.hearts), check.hearts.len());
+        assert_eq!(usize::from(check.actual.dots), check.dots.len());
+    }
+    let failed: Vec<usize> = explanation
+        .clues
+        .iter()
+        .filter(|check| !check.ok())
+        .map(|check| check.clue)
+        .collect();
+    assert_eq!(failed, [1, 2, 4]);
+    assert_eq!(
+        explain_guess(b"cgr", &puzzle),
+        Err(SolveError::GuessLength {
+            expected: 5,
+            found: 3
+        })
+    );
+    let seven = Puzzle::from_clues(parse_shortform("rgbyopb22"));
+    let explanation = explain_guess(b"rgbbopb", &seven).unwrap();
+    assert_eq!(
+        explanation.broken_rule,
+        Some(RuleViolation::SameNeighbours(2, 3))
+    );
+}
@@ -0,0 +1,221 @@
+//! Makes up puzzles with exactly one solution, for practice.
+//!
+//! A secret is picked at random, and clues are made by comparing random guesses against it,
+//! until the clues leave nothing but the secret.
+
+use crate::{
+    Clue, Peg, Pegs, Puzzle, Rules, SUPPORTED_PEG_COUNTS,
+    backtrack::{Backtrack, count},
+    bruteforce::compare,
+};
+use std::fmt;
+
+/// Solutions are only counted up to this many while generating.
+///
+/// Above it, every clue is assumed to narrow things down.
+const COUNT_CAP: u64 = 10_000;
+
+/// How hard a generated puzzle should be
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// Few clues that each give away a lot
+    Easy,
+    #[default]
+    Medium,
+    /// Many clues that each give away little, and have to be combined
+    Hard,
+}
+
+impl Difficulty {
+    /// How many random guesses to pick each clue from.
+    ///
+    /// Easy takes the one that narrows things down most, Medium the middle one, and Hard
+    /// the one that narrows things down least, so the harder the more there are to pick from.
+    fn tries(self) -> usize {
+        match self {
+            Self::Easy => 4,
+            Self::Medium => 6,
+            Self::Hard => 8,
+        }
+    }
+}
+
+/// Reasons why a puzzle can't be generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The size isn't in [`SUPPORTED_PEG_COUNTS`]
+    UnsupportedPegCount(usize),
+    /// The palette allows fewer than two guesses that follow the rules for the size, so
+    /// there's nothing to give a clue with
+    PaletteTooSmall,
+    /// The palette has a byte that isn't an ASCII letter, which the short form can't write
+    InvalidPeg(Peg),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::UnsupportedPegCount(count) => write!(f, "{count} peg puzzles are not supported"),
+            Self::PaletteTooSmall => f.write_str("Not enough colors for a valid solution"),
+            Self::InvalidPeg(peg) => {
+                write!(f, "{:?} can't be a peg, only letters can", char::from(peg))
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// Makes up a puzzle of `size` pegs with colours from `palette`, which has exactly one solution.
+///
+/// Clues use only colours from the palette, and both the secret and the clues follow the
+/// game's rules for the size. Palette colours that no clue uses end up as free pegs.
+/// The same arguments always give the same puzzle.
+///
+/// # Errors
+///
+/// If `size` isn't supported, `palette` has something other than letters, or it doesn't
+/// have enough colours for a clue and a different secret
+pub fn generate_puzzle(
+    size: usize,
+    palette: &Pegs,
+    difficulty: Difficulty,
+    seed: u64,
+) -> Result<Puzzle, GenerateError> {
+    if !SUPPORTED_PEG_COUNTS.contains(&size) {
+        return Err(GenerateError::UnsupportedPegCount(size));
+    }
+    if let Some(&peg) = palette.iter().find(|peg| !peg.is_ascii_alphabetic()) {
+        return Err(GenerateError::InvalidPeg(peg));
+    }
+    let rules = Rules::for_size(size);
+    let mut palette = palette.to_vec();
+    palette.sort_unstable();
+    palette.dedup();
+    if Backtrack::new(&palette, &[], size, rules).take(2).count() < 2 {
+        return Err(GenerateError::PaletteTooSmall);
+    }
+    let mut rng = Rng(seed);
+    let secret = rng.guess(&palette, size, rules);
+    let mut puzzle = Puzzle::new(size, Vec::new());
+    puzzle.free_pegs.clone_from(&palette);
+    let mut remaining = COUNT_CAP;
+    while remaining > 1 {
+        let clue_for = |guess: Vec<Peg>| Clue {
+            indicator: compare(&guess, &secret),
+            pegs: guess.into_boxed_slice(),
+        };
+        let mut tried: Vec<(u64, Clue)> = (0..difficulty.tries())
+            .filter_map(|_| {
+                let guess = rng.guess(&palette, size, rules);
+                (guess != secret).then(|| {
+                    let clue = clue_for(guess);
+                    (count_with(&puzzle, &clue), clue)
+                })
+            })
+            .filter(|&(count, _)| count < remaining || remaining == COUNT_CAP)
+            .collect();
+        tried.sort_by_key(|&(count, _)| count);
+        let picked = match difficulty {
+            Difficulty::Easy => tried.into_iter().next(),
+            Difficulty::Medium => (!tried.is_empty()).then(|| tried.swap_remove(tried.len() / 2)),
+            // A clue that leaves the count at the cap might not narrow anything down at all
+            Difficulty::Hard => match tried.iter().rposition(|&(count, _)| count < remaining) {
+                Some(i) => Some(tried.swap_remove(i)),
+                None => tried.into_iter().next(),
+            },
+        };
+        // A solution other than the secret can't survive its own clue
+        let Some((count, clue)) = picked.or_else(|| {
+            Backtrack::new(&puzzle.free_pegs, &puzzle.clues, size, rules)
+                .find(|guess| *guess != secret)
+                .map(|other| {
+                    let clue = clue_for(other);
+                    (count_with(&puzzle, &clue), clue)
+                })
+        }) else {
+            break;
+        };
+        puzzle.clues.push(clue);
+        remaining = count;
+    }
+    puzzle
+        .free_pegs
+        .retain(|peg| !puzzle.clues.iter().any(|clue| clue.pegs.contains(peg)));
+    Ok(puzzle)
+}
+
+/// Number of solutions `puzzle` would have with `clue` added, up to [`COUNT_CAP`]
+fn count_with(puzzle: &Puzzle, clue: &Clue) -> u64 {
+    let mut puzzle = puzzle.clone();
+    puzzle.clues.push(clue.clone());
+    count(&puzzle, Some(COUNT_CAP))
+}
+
+/// A small, fast pseudo random number generator (`SplitMix64`)
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    /// A random index below `len`
+    // The result is below `len`, so it fits
+    #[expect(clippy::cast_possible_truncation)]
+    fn below(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+    /// A random guess that follows `rules`.
+    ///
+    /// There has to be at least one.
+    fn guess(&mut self, palette: &Pegs, size: usize, rules: Rules) -> Vec<Peg> {
+        loop {
+            let guess: Vec<Peg> = (0..size)
+                .map(|_| palette[self.below(palette.len())])
+                .collect();
+            if rules.check(&guess).is_none() {
+                return guess;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generate() {
+    use crate::count_solutions;
+    for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+        for (size, palette) in [(3, &b"rgb"[..]), (5, b"rgbyop"), (7, b"rgbyo")] {
+            for seed in 0..3 {
+                let puzzle = generate_puzzle(size, palette, difficulty, seed).unwrap();
+                assert_eq!(count_solutions(&puzzle, None), Ok(1));
+                assert!(puzzle.alphabet().iter().all(|peg| palette.contains(peg)));
+                assert_eq!(generate_puzzle(size, palette, difficulty, seed), Ok(puzzle));
+            }
+        }
+    }
+    assert_eq!(
+        generate_puzzle(7, b"rg", Difficulty::Easy, 0),
+        Err(GenerateError::PaletteTooSmall)
+    );
+    // Only one guess follows the rules, so there's no clue that isn't the secret
+    assert_eq!(
+        generate_puzzle(4, b"r", Difficulty::Hard, 1),
+        Err(GenerateError::PaletteTooSmall)
+    );
+    assert_eq!(
+        generate_puzzle(6, b"rgb", Difficulty::Easy, 0),
+        Err(GenerateError::UnsupportedPegCount(6))
+    );
+    assert_eq!(
+        generate_puzzle(3, b"rg1", Difficulty::Easy, 2),
+        Err(GenerateError::InvalidPeg(b'1'))
+    );
+    assert_eq!(
+        generate_puzzle(3, b"r g", Difficulty::Easy, 0),
+        Err(GenerateError::InvalidPeg(b' '))
+    );
+}
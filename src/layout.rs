@@ -0,0 +1,121 @@
+//! Board shapes: where the slots of a puzzle sit, and which of them are neighbours.
+//!
+//! The solver and the GUI both go by these, so a new shape only has to be added here.
+
+use crate::Pegs;
+
+/// The shape of the board for one puzzle size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Layout {
+    /// Position of each slot as (x, y), in half peg widths, with y pointing down
+    pub positions: &'static [(u8, u8)],
+    /// Pairs of neighbouring slots, with the lower slot first
+    pub neighbours: &'static [(usize, usize)],
+}
+
+impl Layout {
+    pub const ROW_3: Self = Self {
+        positions: &[(0, 0), (2, 0), (4, 0)],
+        neighbours: &[(0, 1), (1, 2)],
+    };
+    pub const ROW_4: Self = Self {
+        positions: &[(0, 0), (2, 0), (4, 0), (6, 0)],
+        neighbours: &[(0, 1), (1, 2), (2, 3)],
+    };
+    pub const ROW_5: Self = Self {
+        positions: &[(0, 0), (2, 0), (4, 0), (6, 0), (8, 0)],
+        neighbours: &[(0, 1), (1, 2), (2, 3), (3, 4)],
+    };
+    /// The hexagonal seven peg layout
+    ///
+    /// ```text
+    ///  [0][1]
+    /// [2][3][4]
+    ///  [5][6]
+    /// ```
+    pub const HEX_7: Self = Self {
+        positions: &[(1, 0), (3, 0), (0, 2), (2, 2), (4, 2), (1, 4), (3, 4)],
+        neighbours: &[
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 5),
+            (3, 4),
+            (3, 5),
+            (3, 6),
+            (4, 6),
+            (5, 6),
+        ],
+    };
+    /// The layouts the game uses, one for each supported puzzle size
+    pub const BUILT_IN: [Self; 4] = [Self::ROW_3, Self::ROW_4, Self::ROW_5, Self::HEX_7];
+
+    /// The layout the game uses for puzzles of `size` pegs
+    #[must_use]
+    pub fn for_size(size: usize) -> Option<Self> {
+        Self::BUILT_IN
+            .into_iter()
+            .find(|layout| layout.len() == size)
+    }
+    /// Number of slots
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.positions.len()
+    }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    /// Width and height of the area the slots take up, in half peg widths
+    #[must_use]
+    pub fn extent(&self) -> (u8, u8) {
+        self.positions
+            .iter()
+            .fold((0, 0), |(w, h), &(x, y)| (w.max(x + 2), h.max(y + 2)))
+    }
+    /// The neighbours of `slot` that come before it
+    pub fn earlier_neighbours(&self, slot: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbours
+            .iter()
+            .filter(move |&&(_, b)| b == slot)
+            .map(|&(a, _)| a)
+    }
+    /// The first pair of neighbouring slots in `pegs` that hold the same peg.
+    ///
+    /// Always `None` if `pegs` doesn't have a peg for every slot.
+    #[must_use]
+    pub fn same_neighbours(&self, pegs: &Pegs) -> Option<(usize, usize)> {
+        if pegs.len() != self.len() {
+            return None;
+        }
+        self.neighbours
+            .iter()
+            .copied()
+            .find(|&(a, b)| pegs[a] == pegs[b])
+    }
+}
+
+#[test]
+fn test_layouts() {
+    use crate::SUPPORTED_PEG_COUNTS;
+    assert_eq!(
+        Layout::BUILT_IN.map(|layout| layout.len()),
+        SUPPORTED_PEG_COUNTS
+    );
+    for layout in Layout::BUILT_IN {
+        for &(a, b) in layout.neighbours {
+            assert!(a < b && b < layout.len());
+        }
+    }
+    assert_eq!(Layout::HEX_7.extent(), (6, 6));
+    assert_eq!(Layout::ROW_4.extent(), (8, 2));
+    assert_eq!(
+        Layout::HEX_7.earlier_neighbours(3).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert_eq!(Layout::HEX_7.same_neighbours(b"rgbbopb"), Some((2, 3)));
+    assert_eq!(Layout::HEX_7.same_neighbours(b"rgbyopb"), None);
+}
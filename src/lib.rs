@@ -1,4 +1,4 @@
-//! Monster mind puzzle solver for 3/4/5 peg puzzles
+//! Monster mind puzzle solver for 3/4/5/7 peg puzzles
 //!
 //! A puzzle of N size consists of:
 //!
@@ -17,18 +17,70 @@
 //! For example, if a clue has a green peg at the first slot, but it has no hearts,
 //! a guess having a green peg at the first slot is not a valid solution, because it contradicts
 //! a clue.
+//!
+//! Every solver yields solutions in ascending lexicographic order of their peg values,
+//! so the "first" solution of a puzzle is always the same one.
+
+pub use short_form::{
+    ParseError, ParseErrorKind, parse as parse_shortform, parse_puzzle as parse_shortform_puzzle,
+    parse_strict as parse_shortform_strict, serialize as to_shortform,
+    serialize_puzzle as puzzle_to_shortform,
+};
+pub use {
+    backtrack::{count_solutions, slot_possibilities, solve_backtrack, solve_backtrack_raw},
+    bruteforce::{solve_bruteforce, solve_bruteforce_raw},
+    combinations::SliceCombo,
+    conflict::{Conflict, find_conflict},
+    explain::{ClueCheck, Explanation, explain_guess},
+    generate::{Difficulty, GenerateError, generate_puzzle},
+    layout::Layout,
+    minimize::{minimize_clues, redundant_clues},
+    parallel::{par_map_ordered, solve_parallel},
+    rating::{Rating, rate_difficulty},
+    rules::{RuleViolation, Rules, RulesError},
+    session::SolverSession,
+    suggest::{GuessScore, SuggestStrategy, suggest_guess},
+    task::{CancelToken, Progress, SolveTask, solve_with_progress},
+    tolerant::{Mismatch, NearSolution, solve_tolerant},
+    unseen::{PatternPeg, SolutionGroup, group_solutions},
+};
 
-pub use bruteforce::{solve_bruteforce, solve_bruteforce_raw};
-pub use short_form::parse as parse_shortform;
+use std::fmt;
 
+mod backtrack;
 mod bruteforce;
 mod combinations;
+mod conflict;
+mod explain;
+mod generate;
+mod layout;
+mod minimize;
+mod parallel;
+mod rating;
+mod rules;
+mod session;
 mod short_form;
+mod suggest;
+mod task;
+mod tolerant;
+mod unseen;
 
 pub type Peg = u8;
 pub type Pegs = [Peg];
 
-#[derive(Debug, Clone)]
+/// The puzzle sizes (pegs per clue) the solver knows how to handle,
+/// one for each [built-in layout](Layout::BUILT_IN)
+pub const SUPPORTED_PEG_COUNTS: [usize; Layout::BUILT_IN.len()] = {
+    let mut counts = [0; Layout::BUILT_IN.len()];
+    let mut i = 0;
+    while i < counts.len() {
+        counts[i] = Layout::BUILT_IN[i].len();
+        i += 1;
+    }
+    counts
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Clue {
     pub indicator: Indicator,
     pub pegs: Box<Pegs>,
@@ -40,6 +92,271 @@ pub struct Indicator {
     pub hearts: u8,
 }
 
+/// Reasons why a set of clues can't be solved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    /// There are no clues at all
+    NoClues,
+    /// The clue at index `clue` has a different number of pegs than the first clue
+    MixedLength {
+        clue: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The hearts and dots of the clue at index `clue` add up to more than its peg count
+    IndicatorTooLarge { clue: usize },
+    /// The clues have a peg count that isn't in [`SUPPORTED_PEG_COUNTS`]
+    UnsupportedPegCount(usize),
+    /// A guess to check has a different number of pegs than the puzzle
+    GuessLength { expected: usize, found: usize },
+    /// The clue at index `clue` has a peg that isn't in the puzzle's palette
+    PegNotInPalette { clue: usize, peg: Peg },
+    /// A free peg isn't in the puzzle's palette
+    FreePegNotInPalette(Peg),
+    /// The layout of the rules has a different number of slots than the puzzle has pegs
+    LayoutMismatch { expected: usize, found: usize },
+    /// A pair of neighbours in the layout doesn't have the lower slot first, or has a slot
+    /// that isn't on the board
+    InvalidNeighbours(usize, usize),
+    /// The rules don't allow any peg to appear at all
+    ZeroMaxRepeats,
+    /// The rules require a number of different colors that `size` pegs can't have
+    ColorsOutOfRange { required: u8, size: usize },
+    /// There are too many candidates to go through them one by one
+    TooManyCandidates,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::NoClues => f.write_str("Can't solve without clues"),
+            Self::MixedLength {
+                clue,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Clue {} has {found} pegs, but the first clue has {expected}",
+                clue + 1
+            ),
+            Self::IndicatorTooLarge { clue } => {
+                write!(f, "Clue {} has more hearts and dots than pegs", clue + 1)
+            }
+            Self::UnsupportedPegCount(count) => write!(f, "{count} peg puzzles are not supported"),
+            Self::GuessLength { expected, found } => {
+                write!(f, "Guess has {found} pegs, but the puzzle has {expected}")
+            }
+            Self::PegNotInPalette { clue, peg } => write!(
+                f,
+                "Clue {} has {}, which isn't in the palette",
+                clue + 1,
+                char::from(peg)
+            ),
+            Self::FreePegNotInPalette(peg) => {
+                write!(f, "Free peg {} isn't in the palette", char::from(peg))
+            }
+            Self::LayoutMismatch { expected, found } => write!(
+                f,
+                "The layout has {found} slots, but the puzzle has {expected} pegs"
+            ),
+            Self::InvalidNeighbours(a, b) => write!(
+                f,
+                "The layout has slots {} and {} as neighbours, but neighbours need the lower \
+                 slot first and both on the board",
+                a + 1,
+                b + 1
+            ),
+            Self::ZeroMaxRepeats => f.write_str("The rules don't let any peg appear even once"),
+            Self::ColorsOutOfRange { required, size } => write!(
+                f,
+                "The rules require {required} different colors, which {size} pegs can't have"
+            ),
+            Self::TooManyCandidates => f.write_str("Too many candidates to try them all"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Checks that `clues` make up a well-formed puzzle of `size` pegs
+fn check_clues(size: usize, clues: &[Clue]) -> Result<(), SolveError> {
+    if clues.is_empty() {
+        return Err(SolveError::NoClues);
+    }
+    if !SUPPORTED_PEG_COUNTS.contains(&size) {
+        return Err(SolveError::UnsupportedPegCount(size));
+    }
+    for (i, clue) in clues.iter().enumerate() {
+        if clue.pegs.len() != size {
+            return Err(SolveError::MixedLength {
+                clue: i,
+                expected: size,
+                found: clue.pegs.len(),
+            });
+        }
+        if usize::from(clue.indicator.hearts) + usize::from(clue.indicator.dots) > size {
+            return Err(SolveError::IndicatorTooLarge { clue: i });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `rules` are for a board of `size` slots, with neighbours as
+/// [`Layout::neighbours`] describes them, and can be met at all
+fn check_rules(size: usize, rules: Rules) -> Result<(), SolveError> {
+    if rules.layout.len() != size {
+        return Err(SolveError::LayoutMismatch {
+            expected: size,
+            found: rules.layout.len(),
+        });
+    }
+    match rules
+        .layout
+        .neighbours
+        .iter()
+        .find(|&&(a, b)| a >= b || b >= size)
+    {
+        Some(&(a, b)) => Err(SolveError::InvalidNeighbours(a, b)),
+        None => rules.check_ranges(),
+    }
+}
+
+/// A complete puzzle, with everything the solver needs to know about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    /// Number of pegs in the solution, and in each clue
+    pub size: usize,
+    pub clues: Vec<Clue>,
+    /// Pegs that may appear in the solution, even if they don't appear in any clue
+    pub free_pegs: Vec<Peg>,
+    /// All pegs in play, if known.
+    ///
+    /// They can all appear in the solution, and clues can't have any other pegs.
+    pub palette: Option<Vec<Peg>>,
+    pub rules: Rules,
+}
+
+impl Puzzle {
+    /// A puzzle of `size` pegs, with the game's rules for that size, and no free pegs
+    #[must_use]
+    pub fn new(size: usize, clues: Vec<Clue>) -> Self {
+        Self {
+            size,
+            clues,
+            free_pegs: Vec::new(),
+            palette: None,
+            rules: Rules::for_size(size),
+        }
+    }
+    /// Like [`Puzzle::new`], but the size is taken from the first clue
+    #[must_use]
+    pub fn from_clues(clues: Vec<Clue>) -> Self {
+        Self::new(clues.first().map_or(0, |clue| clue.pegs.len()), clues)
+    }
+    /// Checks whether the puzzle is well-formed enough to be solved
+    ///
+    /// # Errors
+    ///
+    /// If it isn't
+    pub fn validate(&self) -> Result<(), SolveError> {
+        check_clues(self.size, &self.clues)?;
+        check_rules(self.size, self.rules)?;
+        if let Some(palette) = &self.palette {
+            for (i, clue) in self.clues.iter().enumerate() {
+                if let Some(&peg) = clue.pegs.iter().find(|peg| !palette.contains(peg)) {
+                    return Err(SolveError::PegNotInPalette { clue: i, peg });
+                }
+            }
+            if let Some(&peg) = self.free_pegs.iter().find(|peg| !palette.contains(peg)) {
+                return Err(SolveError::FreePegNotInPalette(peg));
+            }
+        }
+        Ok(())
+    }
+    /// The pegs that may appear in a solution: every peg in the clues, the free pegs,
+    /// and the palette.
+    ///
+    /// Sorted in ascending order.
+    #[must_use]
+    pub fn alphabet(&self) -> Vec<Peg> {
+        match &self.palette {
+            Some(palette) => {
+                bruteforce::alphabet(&[&self.free_pegs[..], palette].concat(), &self.clues)
+            }
+            None => bruteforce::alphabet(&self.free_pegs, &self.clues),
+        }
+    }
+    /// The pegs of the alphabet that no clue has.
+    ///
+    /// As far as the clues can tell these are all alike, see [`group_solutions`].
+    #[must_use]
+    pub fn unseen_pegs(&self) -> Vec<Peg> {
+        let mut pegs = self.alphabet();
+        pegs.retain(|peg| !self.clues.iter().any(|clue| clue.pegs.contains(peg)));
+        pegs
+    }
+    /// Every guess the brute-force strategy considers, whether it's a solution or not.
+    ///
+    /// Useful for paging through or resuming a search with [`Iterator::nth`] and
+    /// [`SliceCombo::index`], together with [`Puzzle::is_solution`].
+    ///
+    /// # Errors
+    ///
+    /// [`SolveError::TooManyCandidates`] if they can't be counted in a `usize`
+    pub fn candidates(&self) -> Result<SliceCombo<Peg, Vec<Peg>>, SolveError> {
+        SliceCombo::new(self.alphabet(), self.size).ok_or(SolveError::TooManyCandidates)
+    }
+    /// The same puzzle, with only the clues for whose index `keep` returns true.
+    ///
+    /// Pegs of the dropped clues become free pegs, so the alphabet stays the same.
+    #[must_use]
+    pub fn retain_clues(&self, mut keep: impl FnMut(usize) -> bool) -> Self {
+        Self {
+            size: self.size,
+            clues: (0..self.clues.len())
+                .filter(|&i| keep(i))
+                .map(|i| self.clues[i].clone())
+                .collect(),
+            free_pegs: self.alphabet(),
+            palette: self.palette.clone(),
+            rules: self.rules,
+        }
+    }
+    /// Whether `guess` contradicts neither the clues nor the rules
+    #[must_use]
+    pub fn is_solution(&self, guess: &Pegs) -> bool {
+        guess.len() == self.size && bruteforce::validate_guess(guess, &self.clues, self.rules)
+    }
+    /// All solutions of the puzzle, in ascending lexicographic order
+    ///
+    /// # Errors
+    ///
+    /// If the puzzle doesn't [validate](Puzzle::validate), or has
+    /// [too many candidates](SolveError::TooManyCandidates)
+    pub fn solutions(&self) -> Result<impl Iterator<Item = Vec<Peg>> + '_, SolveError> {
+        self.validate()?;
+        bruteforce::solutions(&self.alphabet(), &self.clues, self.size, self.rules)
+    }
+    /// Same as [`Puzzle::solutions`], but using the backtracking strategy, which is much faster
+    /// for puzzles with a large search space
+    ///
+    /// # Errors
+    ///
+    /// If the puzzle doesn't [validate](Puzzle::validate)
+    pub fn solutions_backtrack(&self) -> Result<impl Iterator<Item = Vec<Peg>> + '_, SolveError> {
+        self.validate()?;
+        Ok(backtrack::Backtrack::for_puzzle(self))
+    }
+    /// The lexicographically smallest solution of the puzzle, if any
+    ///
+    /// # Errors
+    ///
+    /// If the puzzle doesn't [validate](Puzzle::validate)
+    pub fn solve(&self) -> Result<Option<Vec<Peg>>, SolveError> {
+        Ok(self.solutions()?.next())
+    }
+}
+
 #[derive(Copy, Clone, PartialEq)]
 enum Marker {
     None,
@@ -54,7 +371,87 @@ fn test_solve() {
             &[],
             &parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03")
         )
+        .unwrap()
         .as_deref(),
         Some("cgrgy")
     );
 }
+
+#[test]
+fn test_puzzle() {
+    let mut puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13"));
+    assert_eq!(puzzle.size, 5);
+    assert_eq!(
+        puzzle.solutions().unwrap().next().as_deref(),
+        Some(&b"cggyr"[..])
+    );
+    assert_eq!(puzzle.solutions().unwrap().count(), 10);
+    puzzle.clues.extend(parse_shortform("rccgg13 yrccc03"));
+    assert_eq!(puzzle.solve(), Ok(Some(b"cgrgy".to_vec())));
+    puzzle.size = 7;
+    assert_eq!(
+        puzzle.validate(),
+        Err(SolveError::MixedLength {
+            clue: 0,
+            expected: 7,
+            found: 5
+        })
+    );
+}
+
+#[test]
+fn test_paging() {
+    let puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13"));
+    let all: Vec<_> = puzzle.solutions().unwrap().collect();
+    let mut candidates = puzzle.candidates().unwrap();
+    let mut page = Vec::new();
+    while page.len() < 4
+        && let Some(guess) = candidates.next_ref()
+    {
+        if puzzle.is_solution(guess) {
+            page.push(guess.to_vec());
+        }
+    }
+    assert_eq!(page, all[..4]);
+    // Resume the search where the first page ended
+    let mut rest = puzzle.candidates().unwrap();
+    rest.nth(candidates.index() - 1);
+    let rest: Vec<_> = rest.filter(|guess| puzzle.is_solution(guess)).collect();
+    assert_eq!(rest, all[4..]);
+}
+
+#[test]
+fn test_solve_errors() {
+    assert_eq!(solve_bruteforce(&[], &[]), Err(SolveError::NoClues));
+    assert_eq!(
+        solve_bruteforce(&[], &parse_shortform("ccprg12 cypp11")),
+        Err(SolveError::MixedLength {
+            clue: 1,
+            expected: 5,
+            found: 4
+        })
+    );
+    assert_eq!(
+        solve_bruteforce(&[], &parse_shortform("ccprg12 cyppc33")),
+        Err(SolveError::IndicatorTooLarge { clue: 1 })
+    );
+    assert_eq!(
+        solve_bruteforce(&[], &parse_shortform("ccprgc12")),
+        Err(SolveError::UnsupportedPegCount(6))
+    );
+    let mut puzzle = Puzzle::from_clues(parse_shortform("ccprg12"));
+    puzzle.rules.layout = Layout::HEX_7;
+    assert_eq!(
+        puzzle.validate(),
+        Err(SolveError::LayoutMismatch {
+            expected: 5,
+            found: 7
+        })
+    );
+    puzzle.rules.layout = Layout {
+        neighbours: &[(2, 0)],
+        ..Layout::ROW_5
+    };
+    assert_eq!(puzzle.validate(), Err(SolveError::InvalidNeighbours(2, 0)));
+    assert_eq!(SUPPORTED_PEG_COUNTS, [3, 4, 5, 7]);
+}
@@ -0,0 +1,94 @@
+//! Finds clues that can be left out of a puzzle without changing its solutions.
+
+use crate::{
+    Puzzle, SolveError,
+    backtrack::{Backtrack, count},
+    bruteforce::validate_guess,
+};
+
+/// Indices of the clues that can be removed one at a time without changing the solutions.
+///
+/// Removing two of them at once might still change the solutions, for example when
+/// two clues say the same thing.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn redundant_clues(puzzle: &Puzzle) -> Result<Vec<usize>, SolveError> {
+    puzzle.validate()?;
+    Ok((0..puzzle.clues.len())
+        .filter(|&i| {
+            // Every solution without the clue has to agree with it anyway
+            let without = puzzle.retain_clues(|j| j != i);
+            Backtrack::for_puzzle(&without).all(|guess| {
+                validate_guess(&guess, std::slice::from_ref(&puzzle.clues[i]), puzzle.rules)
+            })
+        })
+        .collect())
+}
+
+/// Indices of a smallest set of clues that still leaves `puzzle` with its one solution.
+///
+/// Returns `None` if the puzzle doesn't have exactly one solution to begin with.
+/// If there are several smallest sets, returns the first one in lexicographic order.
+///
+/// Tries every set of clues by size, smallest first. That's up to `2^n` solves for `n`
+/// clues, so the time grows exponentially: fine for a dozen clues, hopeless for a hundred.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn minimize_clues(puzzle: &Puzzle) -> Result<Option<Vec<usize>>, SolveError> {
+    puzzle.validate()?;
+    if count(puzzle, Some(2)) != 1 {
+        return Ok(None);
+    }
+    let n = puzzle.clues.len();
+    for size in 1..n {
+        let mut subset: Vec<usize> = (0..size).collect();
+        loop {
+            if count(&puzzle.retain_clues(|i| subset.contains(&i)), Some(2)) == 1 {
+                return Ok(Some(subset));
+            }
+            if !next_subset(&mut subset, n) {
+                break;
+            }
+        }
+    }
+    Ok(Some((0..n).collect()))
+}
+
+/// Moves `subset` on to the next set of the same size out of `0..n`, in lexicographic order.
+///
+/// Returns false if it was the last one.
+fn next_subset(subset: &mut [usize], n: usize) -> bool {
+    let len = subset.len();
+    let Some(i) = (0..len).rev().find(|&i| subset[i] < n - len + i) else {
+        return false;
+    };
+    subset[i] += 1;
+    for j in i + 1..len {
+        subset[j] = subset[j - 1] + 1;
+    }
+    true
+}
+
+#[test]
+fn test_minimize() {
+    use crate::parse_shortform;
+    // The first clue twice
+    let mut puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03"));
+    puzzle.clues.push(puzzle.clues[0].clone());
+    let redundant = redundant_clues(&puzzle).unwrap();
+    assert!(redundant.contains(&0));
+    assert!(redundant.contains(&5));
+    let minimal = minimize_clues(&puzzle).unwrap().unwrap();
+    let smaller = puzzle.retain_clues(|i| minimal.contains(&i));
+    assert_eq!(smaller.solve(), Ok(Some(b"cgrgy".to_vec())));
+    assert_eq!(count(&smaller, None), 1);
+    assert!(minimal.len() <= 4);
+    // Every clue of a smallest set is needed
+    assert!(redundant_clues(&smaller).unwrap().is_empty());
+    let ambiguous = puzzle.retain_clues(|i| i < 3);
+    assert_eq!(minimize_clues(&ambiguous), Ok(None));
+}
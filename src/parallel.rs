@@ -0,0 +1,104 @@
+//! Runs the brute-force strategy on all available cores, by splitting the combination space
+//! into contiguous ranges and handing them out to worker threads.
+
+use crate::{Peg, Puzzle, SolveError, bruteforce::Filtered, combinations::SliceCombo};
+use std::{
+    num::NonZero,
+    panic,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// How many ranges each thread gets on average, so threads that finish early can pick up more work
+const PARTS_PER_THREAD: usize = 8;
+
+/// Solves `puzzle` using every available core.
+///
+/// The result is identical to collecting [`Puzzle::solutions`], including the order.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate), or has
+/// [too many candidates](SolveError::TooManyCandidates)
+pub fn solve_parallel(puzzle: &Puzzle) -> Result<Vec<Vec<Peg>>, SolveError> {
+    puzzle.validate()?;
+    let alphabet = puzzle.alphabet();
+    let combos =
+        SliceCombo::new(alphabet.as_slice(), puzzle.size).ok_or(SolveError::TooManyCandidates)?;
+    let solve_part = |part: SliceCombo<Peg, &[Peg]>| -> Vec<Vec<Peg>> {
+        Filtered {
+            combos: part,
+            clues: &puzzle.clues,
+            rules: puzzle.rules,
+        }
+        .collect()
+    };
+    let threads = thread::available_parallelism().map_or(1, NonZero::get);
+    if threads == 1 {
+        return Ok(solve_part(combos));
+    }
+    let parts = combos.split(threads * PARTS_PER_THREAD);
+    let results = par_map_ordered(&parts, threads, |part| solve_part(part.clone()));
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// Calls `f` on every item on `threads` threads, and returns the results in the order of `items`.
+///
+/// Threads take the next item as soon as they're done with one, so uneven items even out.
+/// A panic in `f` is passed on to the caller.
+pub fn par_map_ordered<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            return done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+    results.sort_unstable_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[test]
+fn test_same_as_serial() {
+    use crate::parse_shortform;
+    for (input, free_pegs) in [
+        ("ccprg12 cyppc11 crycg13", "w"),
+        ("rgbyopb34", ""),
+        ("rgb10 bby01", ""),
+    ] {
+        let mut puzzle = Puzzle::from_clues(parse_shortform(input));
+        puzzle.free_pegs = free_pegs.as_bytes().to_vec();
+        let serial: Vec<_> = puzzle.solutions().unwrap().collect();
+        assert_eq!(solve_parallel(&puzzle), Ok(serial), "{input}");
+    }
+}
+
+#[test]
+fn test_par_map_ordered() {
+    let items: Vec<u64> = (0..100).collect();
+    let squares: Vec<u64> = items.iter().map(|i| i * i).collect();
+    for threads in [0, 1, 3, 200] {
+        assert_eq!(par_map_ordered(&items, threads, |i| i * i), squares);
+    }
+}
@@ -0,0 +1,127 @@
+//! Rates how hard a puzzle is for a person to solve.
+//!
+//! The rating looks at how quickly the clues narrow down the solutions, and at how far
+//! a person gets by looking at one clue at a time and crossing off the pegs it rules out
+//! for each slot, the way most players go about it.
+
+use crate::{Puzzle, SolveError, backtrack::Backtrack, count_solutions};
+use std::fmt;
+
+/// Solutions are only counted up to this many while rating
+const COUNT_CAP: u64 = 100_000;
+
+/// How hard a puzzle is, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    /// Number of solutions left after the first clue, the first two clues, and so on.
+    ///
+    /// Counts stop at 100 000.
+    pub remaining: Vec<u64>,
+    /// How many clues, in order, it takes to narrow the solutions down to one,
+    /// if they ever do
+    pub clues_to_unique: Option<usize>,
+    /// Rounds of crossing off pegs that go against a single clue, until nothing more
+    /// can be crossed off
+    pub deduction_rounds: usize,
+    /// Whether crossing off pegs alone leaves a single peg for every slot.
+    ///
+    /// If not, the rest has to be solved by trying things out.
+    pub deduction_complete: bool,
+    /// All of the above rolled into one number. Higher is harder.
+    ///
+    /// It has no fixed scale, and is only good for comparing puzzles.
+    pub score: f64,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1}", self.score)
+    }
+}
+
+/// Rates how hard `puzzle` is to solve by hand.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn rate_difficulty(puzzle: &Puzzle) -> Result<Rating, SolveError> {
+    puzzle.validate()?;
+    let remaining = (1..=puzzle.clues.len())
+        .map(|n| count_solutions(&puzzle.retain_clues(|i| i < n), Some(COUNT_CAP)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let clues_to_unique = remaining.iter().position(|&n| n == 1).map(|i| i + 1);
+    let (deduction_rounds, deduction_complete) = deduce(puzzle);
+    // The counts and clue numbers are nowhere near big enough to lose precision
+    #[expect(clippy::cast_precision_loss)]
+    let score = {
+        let narrowing = remaining
+            .iter()
+            .map(|&n| (n.max(1) as f64).log10())
+            .sum::<f64>()
+            / remaining.len() as f64;
+        let clues = clues_to_unique.unwrap_or(puzzle.clues.len()) as f64;
+        let guesswork = if deduction_complete { 0.0 } else { 3.0 };
+        narrowing + clues / 2.0 + deduction_rounds as f64 + guesswork
+    };
+    Ok(Rating {
+        remaining,
+        clues_to_unique,
+        deduction_rounds,
+        deduction_complete,
+        score,
+    })
+}
+
+/// Crosses off pegs slot by slot, one clue at a time, in rounds.
+///
+/// A peg gets crossed off a slot if no way of filling the other slots with what's left
+/// makes it agree with some clue. Returns the number of rounds that crossed off anything,
+/// and whether every slot ended up with a single peg.
+fn deduce(puzzle: &Puzzle) -> (usize, bool) {
+    let alphabet = puzzle.alphabet();
+    let mut domains = vec![vec![true; alphabet.len()]; puzzle.size];
+    let mut rounds = 0;
+    loop {
+        let mut next = domains.clone();
+        for clue in puzzle.clues.iter().map(std::slice::from_ref) {
+            for (slot, options) in next.iter_mut().enumerate() {
+                for (peg, possible) in options.iter_mut().enumerate() {
+                    if !*possible {
+                        continue;
+                    }
+                    let mut trial = domains.clone();
+                    trial[slot] = (0..alphabet.len()).map(|i| i == peg).collect();
+                    *possible = Backtrack::new(&alphabet, clue, puzzle.size, puzzle.rules)
+                        .with_domains(&trial)
+                        .advance();
+                }
+            }
+        }
+        if next == domains {
+            break;
+        }
+        domains = next;
+        rounds += 1;
+    }
+    let complete = domains
+        .iter()
+        .all(|slot| slot.iter().filter(|&&ok| ok).count() == 1);
+    (rounds, complete)
+}
+
+#[test]
+fn test_rating() {
+    use crate::parse_shortform;
+    let puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03"));
+    let rating = rate_difficulty(&puzzle).unwrap();
+    assert_eq!(rating.remaining.len(), 5);
+    assert_eq!(rating.remaining[2], 10);
+    assert_eq!(rating.remaining[3], 1);
+    assert!(rating.remaining.is_sorted_by(|a, b| a >= b));
+    assert_eq!(rating.clues_to_unique, Some(4));
+    assert!(rating.deduction_rounds > 0);
+    // Fewer clues leave more to work out
+    let fewer = rate_difficulty(&puzzle.retain_clues(|i| i < 3)).unwrap();
+    assert_eq!(fewer.clues_to_unique, None);
+    assert!(!fewer.deduction_complete);
+}
@@ -0,0 +1,272 @@
+//! Constraints on what a solution may look like, besides agreeing with the clues.
+//!
+//! The game itself only forbids equal neighbours on the seven peg board, but other
+//! Mastermind variants limit how often a color may repeat, or how many colors a solution
+//! uses. The options can be given as text, like `no-repeats,colors=4`.
+
+use crate::{Layout, Peg, Pegs, SolveError, backtrack::occurrences};
+use std::fmt;
+
+/// Extra constraints on what a solution may look like, on top of the clues
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rules {
+    /// The shape of the board, which decides which slots are neighbours
+    pub layout: Layout,
+    /// No two neighbouring slots may hold the same peg
+    pub distinct_neighbours: bool,
+    /// No peg may appear more often than this
+    pub max_repeats: Option<u8>,
+    /// Exactly this many different pegs must appear
+    pub required_colors: Option<u8>,
+}
+
+/// A rule that a guess breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleViolation {
+    /// The two slots are neighbours, and hold the same peg
+    SameNeighbours(usize, usize),
+    /// `peg` appears `count` times, but only `max` are allowed
+    TooManyRepeats { peg: Peg, count: usize, max: u8 },
+    /// The guess has `found` different pegs instead of `expected`
+    ColorCount { expected: u8, found: usize },
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::SameNeighbours(a, b) => write!(
+                f,
+                "neighbouring slots {} and {} hold the same peg",
+                a + 1,
+                b + 1
+            ),
+            Self::TooManyRepeats { peg, count, max } => write!(
+                f,
+                "{} appears {count} times, but at most {max} are allowed",
+                char::from(peg)
+            ),
+            Self::ColorCount { expected, found } => {
+                write!(f, "{found} different colors instead of {expected}")
+            }
+        }
+    }
+}
+
+/// Rule options that can't be understood
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    /// There's no option by this name
+    UnknownOption(String),
+    /// The option needs a number, but got something else
+    InvalidNumber(String),
+    /// The number can't be met by any guess, like no repeats at all, or more colors
+    /// than slots
+    OutOfRange(String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownOption(opt) => write!(f, "Unknown rule option: {opt}"),
+            Self::InvalidNumber(opt) => write!(f, "Rule option needs a number: {opt}"),
+            Self::OutOfRange(opt) => write!(f, "Rule option is out of range: {opt}"),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl Rules {
+    /// The rules the game uses for puzzles of `size` pegs.
+    ///
+    /// Only the hexagonal seven peg layout forbids equal neighbours.
+    #[must_use]
+    pub fn for_size(size: usize) -> Self {
+        let layout = Layout::for_size(size).unwrap_or_default();
+        Self {
+            layout,
+            distinct_neighbours: layout == Layout::HEX_7,
+            ..Self::default()
+        }
+    }
+    /// These rules, changed by the comma separated options in `spec`:
+    ///
+    /// - `no-repeats`: no peg may appear more than once
+    /// - `max-repeats=N`: no peg may appear more than N times, at least once
+    /// - `colors=N`: exactly N different pegs must appear, from 1 up to the number of slots
+    ///   in the layout
+    /// - `distinct-neighbours` or `any-neighbours`: whether neighbouring slots
+    ///   may hold the same peg
+    ///
+    /// # Errors
+    ///
+    /// If an option isn't one of the above, or its number is out of range
+    pub fn with_options(mut self, spec: &str) -> Result<Self, RulesError> {
+        for opt in spec.split(',').map(str::trim).filter(|opt| !opt.is_empty()) {
+            let number = |value: &str| {
+                value
+                    .parse()
+                    .map_err(|_| RulesError::InvalidNumber(opt.to_owned()))
+            };
+            match opt.split_once('=') {
+                None if opt == "no-repeats" => self.max_repeats = Some(1),
+                None if opt == "distinct-neighbours" => self.distinct_neighbours = true,
+                None if opt == "any-neighbours" => self.distinct_neighbours = false,
+                Some(("max-repeats", value)) => self.max_repeats = Some(number(value)?),
+                Some(("colors", value)) => self.required_colors = Some(number(value)?),
+                _ => return Err(RulesError::UnknownOption(opt.to_owned())),
+            }
+            if self.check_ranges().is_err() {
+                return Err(RulesError::OutOfRange(opt.to_owned()));
+            }
+        }
+        Ok(self)
+    }
+    /// Checks that some guess on the layout can meet the numbers in the rules
+    ///
+    /// # Errors
+    ///
+    /// If `max_repeats` is 0, or `required_colors` is 0 or more than the layout has slots
+    pub fn check_ranges(&self) -> Result<(), SolveError> {
+        if self.max_repeats == Some(0) {
+            return Err(SolveError::ZeroMaxRepeats);
+        }
+        if let Some(required) = self.required_colors
+            && (required == 0 || usize::from(required) > self.layout.len())
+        {
+            return Err(SolveError::ColorsOutOfRange {
+                required,
+                size: self.layout.len(),
+            });
+        }
+        Ok(())
+    }
+    /// The first rule `guess` breaks, if any
+    #[must_use]
+    pub fn check(&self, guess: &Pegs) -> Option<RuleViolation> {
+        if self.distinct_neighbours
+            && let Some((a, b)) = self.layout.same_neighbours(guess)
+        {
+            return Some(RuleViolation::SameNeighbours(a, b));
+        }
+        if let Some(max) = self.max_repeats {
+            for (i, &peg) in guess.iter().enumerate() {
+                let count = occurrences(guess, peg);
+                if !guess[..i].contains(&peg) && count > usize::from(max) {
+                    return Some(RuleViolation::TooManyRepeats { peg, count, max });
+                }
+            }
+        }
+        if let Some(expected) = self.required_colors {
+            let found = distinct_count(guess);
+            if found != usize::from(expected) {
+                return Some(RuleViolation::ColorCount { expected, found });
+            }
+        }
+        None
+    }
+}
+
+/// Shows the options that are set, in the form [`Rules::with_options`] takes.
+///
+/// The layout isn't shown.
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut opts = Vec::new();
+        if self.distinct_neighbours {
+            opts.push("distinct-neighbours".to_owned());
+        }
+        match self.max_repeats {
+            Some(1) => opts.push("no-repeats".to_owned()),
+            Some(max) => opts.push(format!("max-repeats={max}")),
+            None => {}
+        }
+        if let Some(colors) = self.required_colors {
+            opts.push(format!("colors={colors}"));
+        }
+        f.write_str(&opts.join(","))
+    }
+}
+
+/// Number of different pegs in `pegs`
+pub(crate) fn distinct_count(pegs: &Pegs) -> usize {
+    pegs.iter()
+        .enumerate()
+        .filter(|&(i, peg)| !pegs[..i].contains(peg))
+        .count()
+}
+
+#[test]
+fn test_rules() {
+    use crate::{Puzzle, parse_shortform};
+    let rules = Rules::for_size(5)
+        .with_options("no-repeats, colors=4")
+        .unwrap();
+    assert_eq!(rules.to_string(), "no-repeats,colors=4");
+    assert_eq!(
+        Rules::for_size(5).with_options(&rules.to_string()),
+        Ok(rules)
+    );
+    assert_eq!(
+        rules.check(b"rgbrg"),
+        Some(RuleViolation::TooManyRepeats {
+            peg: b'r',
+            count: 2,
+            max: 1
+        })
+    );
+    assert_eq!(
+        rules.check(b"rgbyo"),
+        Some(RuleViolation::ColorCount {
+            expected: 4,
+            found: 5
+        })
+    );
+    assert_eq!(
+        Rules::for_size(5).with_options("colors=x"),
+        Err(RulesError::InvalidNumber("colors=x".into()))
+    );
+    assert_eq!(
+        Rules::for_size(5).with_options("bulls"),
+        Err(RulesError::UnknownOption("bulls".into()))
+    );
+    for spec in ["max-repeats=0", "colors=0", "colors=6"] {
+        assert_eq!(
+            Rules::for_size(5).with_options(spec),
+            Err(RulesError::OutOfRange(spec.into()))
+        );
+    }
+    // Set directly, the same numbers fail to validate
+    let mut puzzle = Puzzle::from_clues(parse_shortform("cpr10"));
+    puzzle.rules.max_repeats = Some(0);
+    assert_eq!(puzzle.validate(), Err(SolveError::ZeroMaxRepeats));
+    puzzle.rules.max_repeats = None;
+    puzzle.rules.required_colors = Some(5);
+    assert_eq!(
+        puzzle.validate(),
+        Err(SolveError::ColorsOutOfRange {
+            required: 5,
+            size: 3
+        })
+    );
+    // The rules are enforced while searching, and agree with checking afterwards
+    let mut puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11"));
+    puzzle.free_pegs = b"bw".to_vec();
+    for spec in [
+        "no-repeats",
+        "max-repeats=2",
+        "colors=3",
+        "max-repeats=3,colors=2",
+    ] {
+        puzzle.rules = Rules::for_size(5).with_options(spec).unwrap();
+        let backtrack: Vec<_> = puzzle.solutions_backtrack().unwrap().collect();
+        let bruteforce: Vec<_> = puzzle.solutions().unwrap().collect();
+        assert!(!backtrack.is_empty(), "{spec}");
+        assert_eq!(backtrack, bruteforce, "{spec}");
+        assert!(
+            backtrack
+                .iter()
+                .all(|sol| puzzle.rules.check(sol).is_none())
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Solving a puzzle while it's being played, one clue at a time.
+//!
+//! A game adds one clue per turn, and the candidates that agree with all clues so far only
+//! get fewer. So instead of searching from scratch for every new clue, a session keeps the
+//! candidates that are left after each clue, and only filters those. That work can also
+//! be done a bit at a time with [`SolverSession::step`], so a game loop doesn't stall.
+//!
+//! Candidates are kept as their [index](SliceCombo::index) among all guesses, which takes
+//! less memory than their pegs and doesn't grow with the size of the puzzle.
+
+use crate::{
+    Clue, Peg, Pegs, Puzzle, Rules, SolveError,
+    bruteforce::{alphabet, compare, validate_guess},
+    check_clues, check_rules,
+    combinations::{SliceCombo, total},
+};
+
+/// A puzzle that clues can be added to and removed from, keeping its candidates up to date
+#[derive(Debug, Clone)]
+pub struct SolverSession {
+    size: usize,
+    rules: Rules,
+    /// Sorted in ascending order
+    alphabet: Vec<Peg>,
+    clues: Vec<Clue>,
+    /// The indices of the candidates left after each clue, in ascending order
+    levels: Vec<Vec<u32>>,
+    /// The start of the next level, while it's being worked out
+    partial: Vec<u32>,
+    /// Where the next level is being worked out from: an index among all guesses for the
+    /// first level, otherwise a position in the level before
+    cursor: usize,
+}
+
+impl SolverSession {
+    /// A session without clues, for solutions of `size` pegs out of `palette`
+    #[must_use]
+    pub fn new(size: usize, palette: &Pegs, rules: Rules) -> Self {
+        Self {
+            size,
+            rules,
+            alphabet: alphabet(palette, &[]),
+            clues: Vec::new(),
+            levels: Vec::new(),
+            partial: Vec::new(),
+            cursor: 0,
+        }
+    }
+    /// A session with the clues of `puzzle`, and its [alphabet](Puzzle::alphabet) as palette.
+    ///
+    /// # Errors
+    ///
+    /// If the puzzle doesn't [validate](Puzzle::validate)
+    pub fn for_puzzle(puzzle: &Puzzle) -> Result<Self, SolveError> {
+        puzzle.validate()?;
+        let mut session = Self::new(puzzle.size, &puzzle.alphabet(), puzzle.rules);
+        session.set_clues(&puzzle.clues)?;
+        Ok(session)
+    }
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    #[must_use]
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+    /// The pegs candidates are made of, in ascending order
+    #[must_use]
+    pub fn alphabet(&self) -> &[Peg] {
+        &self.alphabet
+    }
+    #[must_use]
+    pub fn clues(&self) -> &[Clue] {
+        &self.clues
+    }
+    /// Whether the candidates have been worked out for every clue.
+    ///
+    /// Only after [`SolverSession::queue_clues`] they might not be, until
+    /// [`SolverSession::step`] catches up.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.levels.len() == self.clues.len()
+    }
+    /// Number of candidates that agree with every clue.
+    ///
+    /// `None` while there are no clues, as every guess is a candidate then, or while the
+    /// session isn't [ready](SolverSession::is_ready).
+    #[must_use]
+    pub fn candidate_count(&self) -> Option<usize> {
+        self.last_level().map(Vec::len)
+    }
+    /// The candidates that agree with every clue, in ascending order.
+    ///
+    /// `None` while there are no clues, as every guess is a candidate then, or while the
+    /// session isn't [ready](SolverSession::is_ready).
+    #[must_use]
+    pub fn candidates(&self) -> Option<impl Iterator<Item = Vec<Peg>> + '_> {
+        let level = self.last_level()?;
+        let mut combos = self.combos();
+        let mut next = 0;
+        Some(level.iter().map_while(move |&index| {
+            let skip = index as usize - next;
+            next = index as usize + 1;
+            combos.nth(skip)
+        }))
+    }
+    /// Like [`slot_possibilities`](crate::slot_possibilities), but for the candidates
+    /// that agree with every clue.
+    ///
+    /// `None` while there are no clues, or the session isn't [ready](SolverSession::is_ready).
+    #[must_use]
+    pub fn slot_possibilities(&self) -> Option<Vec<Vec<(Peg, u64)>>> {
+        let mut counts = vec![vec![0; self.alphabet.len()]; self.size];
+        self.for_each_candidate(self.last_level()?, |_, guess| {
+            for (slot, peg) in counts.iter_mut().zip(guess) {
+                // The alphabet is sorted, and every candidate is made of it
+                if let Ok(idx) = self.alphabet.binary_search(peg) {
+                    slot[idx] += 1;
+                }
+            }
+        });
+        Some(
+            counts
+                .into_iter()
+                .map(|slot| {
+                    self.alphabet
+                        .iter()
+                        .zip(slot)
+                        .filter(|&(_, count)| count != 0)
+                        .map(|(&peg, count)| (peg, count))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+    /// Adds a clue, and returns the number of candidates left.
+    ///
+    /// The first clue takes a search through every guess, later ones only go through
+    /// the candidates.
+    ///
+    /// # Errors
+    ///
+    /// If the clue doesn't fit the session, like a [`Puzzle`] would
+    /// [fail to validate](Puzzle::validate), or there are more than `u32::MAX` guesses
+    /// to keep track of
+    pub fn add_clue(&mut self, clue: Clue) -> Result<usize, SolveError> {
+        self.queue_clue(clue)?;
+        self.step(usize::MAX);
+        Ok(self.levels[self.clues.len() - 1].len())
+    }
+    /// Removes the clue at `index` and returns it.
+    ///
+    /// The candidates go back to what they were before it was added, and only the clues
+    /// after it are applied again.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds
+    pub fn remove_clue(&mut self, index: usize) -> Clue {
+        let clue = self.clues.remove(index);
+        self.forget_levels(index);
+        self.step(usize::MAX);
+        clue
+    }
+    /// Replaces the clues with `clues`, keeping the work done for the clues they start with.
+    ///
+    /// Editing the last clue only filters the candidates of the ones before it again.
+    ///
+    /// # Errors
+    ///
+    /// Like [`SolverSession::add_clue`]. The clues before the failing one are kept.
+    pub fn set_clues(&mut self, clues: &[Clue]) -> Result<(), SolveError> {
+        let queued = self.queue_clues(clues);
+        self.step(usize::MAX);
+        queued
+    }
+    /// Like [`SolverSession::set_clues`], but leaves working out the candidates to
+    /// [`SolverSession::step`].
+    ///
+    /// Work already done for the clues `clues` start with is kept, even if it isn't finished.
+    ///
+    /// # Errors
+    ///
+    /// Like [`SolverSession::set_clues`]
+    pub fn queue_clues(&mut self, clues: &[Clue]) -> Result<(), SolveError> {
+        let common = self
+            .clues
+            .iter()
+            .zip(clues)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.clues.truncate(common);
+        self.forget_levels(common);
+        for clue in &clues[common..] {
+            self.queue_clue(clue.clone())?;
+        }
+        Ok(())
+    }
+    /// Checks up to `budget` more candidates against the clues they haven't been checked
+    /// against yet, and returns whether the session [is ready](SolverSession::is_ready).
+    // Indices fit in a `u32`, see `queue_clue`
+    #[expect(clippy::cast_possible_truncation)]
+    pub fn step(&mut self, mut budget: usize) -> bool {
+        while budget > 0 && !self.is_ready() {
+            let clue = &self.clues[self.levels.len()];
+            let mut partial = std::mem::take(&mut self.partial);
+            let (checked, end) = if let Some(prev) = self.levels.last() {
+                let end = prev.len().min(self.cursor.saturating_add(budget));
+                self.for_each_candidate(&prev[self.cursor..end], |index, guess| {
+                    if compare(guess, &clue.pegs) == clue.indicator {
+                        partial.push(index);
+                    }
+                });
+                (end - self.cursor, prev.len())
+            } else {
+                let mut combos = SliceCombo::with_range(
+                    &self.alphabet[..],
+                    self.size,
+                    self.cursor..self.cursor.saturating_add(budget),
+                );
+                loop {
+                    let index = combos.index();
+                    let Some(guess) = combos.next_ref() else {
+                        break;
+                    };
+                    if validate_guess(guess, std::slice::from_ref(clue), self.rules) {
+                        partial.push(index as u32);
+                    }
+                }
+                let end = total(self.alphabet.len(), self.size).unwrap_or_default();
+                (combos.index() - self.cursor, end)
+            };
+            budget -= checked;
+            self.cursor += checked;
+            if self.cursor == end {
+                self.levels.push(partial);
+                self.cursor = 0;
+            } else {
+                self.partial = partial;
+            }
+        }
+        self.is_ready()
+    }
+    /// Checks that `clue` fits the session and adds it, without working anything out yet
+    fn queue_clue(&mut self, clue: Clue) -> Result<(), SolveError> {
+        let index = self.clues.len();
+        if total(self.alphabet.len(), self.size).is_none_or(|total| u32::try_from(total).is_err()) {
+            return Err(SolveError::TooManyCandidates);
+        }
+        if let Some(&peg) = clue.pegs.iter().find(|peg| !self.alphabet.contains(peg)) {
+            return Err(SolveError::PegNotInPalette { clue: index, peg });
+        }
+        self.clues.push(clue);
+        if let Err(e) =
+            check_clues(self.size, &self.clues).and_then(|()| check_rules(self.size, self.rules))
+        {
+            self.clues.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+    /// Drops the candidates worked out for the clues from `index` on
+    fn forget_levels(&mut self, index: usize) {
+        if index <= self.levels.len() {
+            self.levels.truncate(index);
+            self.partial.clear();
+            self.cursor = 0;
+        }
+    }
+    /// The candidates that agree with every clue, once they're all worked out
+    fn last_level(&self) -> Option<&Vec<u32>> {
+        self.levels.last().filter(|_| self.is_ready())
+    }
+    /// All guesses, in the order of their indices
+    fn combos(&self) -> SliceCombo<Peg, &[Peg]> {
+        SliceCombo::with_range(&self.alphabet[..], self.size, 0..usize::MAX)
+    }
+    /// Calls `f` with the index and pegs of every candidate in `level`
+    fn for_each_candidate(&self, level: &[u32], mut f: impl FnMut(u32, &Pegs)) {
+        let mut combos = self.combos();
+        let mut next = 0;
+        for &index in level {
+            if let Some(guess) = combos.nth_ref(index as usize - next) {
+                f(index, guess);
+            }
+            next = index as usize + 1;
+        }
+    }
+}
+
+#[test]
+fn test_session() {
+    use crate::parse_shortform;
+    let clues = parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03");
+    let puzzle = Puzzle::from_clues(clues.clone());
+    let mut session = SolverSession::new(5, &puzzle.alphabet(), puzzle.rules);
+    assert_eq!(session.candidate_count(), None);
+    for n in 1..=clues.len() {
+        let count = session.add_clue(clues[n - 1].clone()).unwrap();
+        let partial = puzzle.retain_clues(|i| i < n);
+        let expected: Vec<_> = partial.solutions_backtrack().unwrap().collect();
+        assert_eq!(count, expected.len());
+        assert!(session.candidates().unwrap().eq(expected));
+    }
+    assert_eq!(
+        session.candidates().unwrap().collect::<Vec<_>>(),
+        [b"cgrgy"]
+    );
+    assert_eq!(
+        session.slot_possibilities(),
+        Some(crate::slot_possibilities(&puzzle).unwrap())
+    );
+    // Taking out a clue in the middle gives the same as never having had it
+    let removed = session.remove_clue(1);
+    assert_eq!(removed, clues[1]);
+    let without = puzzle.retain_clues(|i| i != 1);
+    assert_eq!(
+        session.candidate_count(),
+        Some(without.solutions_backtrack().unwrap().count())
+    );
+    // Editing the last clue keeps the others
+    let mut edited = clues.clone();
+    edited[4] = parse_shortform("yrccc12").remove(0);
+    session.set_clues(&edited).unwrap();
+    assert_eq!(session.clues(), edited);
+    assert_eq!(session.candidate_count(), Some(0));
+    assert_eq!(
+        session.add_clue(parse_shortform("bbbbb00").remove(0)),
+        Err(SolveError::PegNotInPalette { clue: 5, peg: b'b' })
+    );
+    assert_eq!(
+        SolverSession::for_puzzle(&puzzle)
+            .unwrap()
+            .candidate_count(),
+        Some(1)
+    );
+    // A bit at a time gives the same, and editing a clue keeps the work for the ones before
+    let mut stepped = SolverSession::new(5, &puzzle.alphabet(), puzzle.rules);
+    stepped.queue_clues(&clues[..3]).unwrap();
+    assert!(!stepped.step(1000));
+    assert_eq!(stepped.candidate_count(), None);
+    stepped.queue_clues(&clues).unwrap();
+    let mut steps = 1;
+    while !stepped.step(1000) {
+        steps += 1;
+    }
+    assert!(steps > 1);
+    assert!(stepped.candidates().unwrap().eq([b"cgrgy".to_vec()]));
+    stepped.queue_clues(&edited).unwrap();
+    assert!(stepped.step(1000));
+    assert_eq!(stepped.candidate_count(), Some(0));
+}
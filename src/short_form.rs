@@ -1,4 +1,5 @@
-use crate::{Clue, Indicator};
+use crate::{Clue, Indicator, Peg, Puzzle};
+use std::fmt;
 
 enum ParseState {
     Init,
@@ -38,3 +39,325 @@ pub fn parse(arg: &str) -> Vec<Clue> {
     }
     clues
 }
+
+/// Writes `clues` in the short form accepted by [`parse`] and [`parse_strict`]
+#[must_use]
+pub fn serialize(clues: &[Clue]) -> String {
+    let mut out = String::new();
+    for (i, clue) in clues.iter().enumerate() {
+        if i != 0 {
+            out.push(' ');
+        }
+        out.push_str(&clue.to_string());
+    }
+    out
+}
+
+/// Writes the clues and free pegs of `puzzle` in the short form accepted by [`parse_puzzle`].
+///
+/// The free pegs come last, after a `+`. Rules and palette aren't included.
+#[must_use]
+pub fn serialize_puzzle(puzzle: &Puzzle) -> String {
+    let mut out = serialize(&puzzle.clues);
+    if !puzzle.free_pegs.is_empty() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push('+');
+        out.extend(puzzle.free_pegs.iter().map(|&peg| char::from(peg)));
+    }
+    out
+}
+
+impl fmt::Display for Indicator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.hearts, self.dots)
+    }
+}
+
+impl fmt::Display for Clue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &peg in &self.pegs {
+            write!(f, "{}", char::from(peg))?;
+        }
+        self.indicator.fmt(f)
+    }
+}
+
+/// Why a short form string was rejected by [`parse_strict`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A byte that has no meaning in the short form
+    UnexpectedByte(u8),
+    /// An indicator without any pegs in front of it
+    MissingPegs,
+    /// Pegs that aren't followed by hearts and dots
+    MissingIndicator,
+    /// A hearts digit that isn't followed by a dots digit
+    MissingDots,
+    /// A clue with a different number of pegs than the first clue
+    InconsistentPegCount { expected: usize, found: usize },
+    /// Hearts and dots that add up to more than the number of pegs
+    IndicatorTooLarge,
+    /// A `+` that isn't followed by any free pegs
+    MissingFreePegs,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::UnexpectedByte(b) if b.is_ascii_graphic() => {
+                write!(f, "unexpected character '{}'", char::from(b))
+            }
+            Self::UnexpectedByte(b) => write!(f, "unexpected byte 0x{b:02x}"),
+            Self::MissingPegs => f.write_str("hearts and dots without any pegs"),
+            Self::MissingIndicator => f.write_str("missing hearts and dots"),
+            Self::MissingDots => f.write_str("missing dots digit after hearts"),
+            Self::InconsistentPegCount { expected, found } => write!(
+                f,
+                "clue has {found} pegs, but the first clue has {expected}"
+            ),
+            Self::IndicatorTooLarge => f.write_str("more hearts and dots than pegs"),
+            Self::MissingFreePegs => f.write_str("'+' without any free pegs after it"),
+        }
+    }
+}
+
+/// A short form parse error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset of the offending character in the input.
+    ///
+    /// Equal to the input length if the input ended too early.
+    pub offset: usize,
+    /// Index of the clue being parsed
+    pub clue: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Clue {}, byte {}: {}",
+            self.clue + 1,
+            self.offset,
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Like [`parse`], but rejects anything that isn't well-formed short form.
+///
+/// Clues are separated by whitespace, and each clue is one or more alphabetic pegs,
+/// followed by a hearts digit and a dots digit.
+///
+/// # Errors
+///
+/// On the first malformed clue
+pub fn parse_strict(arg: &str) -> Result<Vec<Clue>, ParseError> {
+    parse_clues(arg, None)
+}
+
+/// Like [`parse_strict`], but also takes free pegs, written as letters after a `+`,
+/// like `ccprg12 cyppc11 +bw`.
+///
+/// The size of the puzzle is taken from the first clue.
+///
+/// # Errors
+///
+/// On the first malformed clue or free peg list
+pub fn parse_puzzle(arg: &str) -> Result<Puzzle, ParseError> {
+    let mut free_pegs = Vec::new();
+    let clues = parse_clues(arg, Some(&mut free_pegs))?;
+    let mut puzzle = Puzzle::from_clues(clues);
+    puzzle.free_pegs = free_pegs;
+    Ok(puzzle)
+}
+
+/// Parses the clues in `arg`, and the free pegs into `free_pegs` if they're allowed
+fn parse_clues(arg: &str, mut free_pegs: Option<&mut Vec<Peg>>) -> Result<Vec<Clue>, ParseError> {
+    let bytes = arg.as_bytes();
+    let mut clues: Vec<Clue> = Vec::new();
+    let mut pos = 0;
+    loop {
+        while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+            pos += 1;
+        }
+        if pos == bytes.len() {
+            return Ok(clues);
+        }
+        let err = |offset, kind| ParseError {
+            offset,
+            clue: clues.len(),
+            kind,
+        };
+        if bytes[pos] == b'+'
+            && let Some(free_pegs) = free_pegs.as_deref_mut()
+        {
+            pos += 1;
+            let start = pos;
+            while bytes.get(pos).is_some_and(u8::is_ascii_alphabetic) {
+                pos += 1;
+            }
+            if let Some(&b) = bytes.get(pos)
+                && !b.is_ascii_whitespace()
+            {
+                return Err(err(pos, ParseErrorKind::UnexpectedByte(b)));
+            }
+            if pos == start {
+                return Err(err(pos, ParseErrorKind::MissingFreePegs));
+            }
+            free_pegs.extend_from_slice(&bytes[start..pos]);
+            // The free pegs come last
+            while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+                pos += 1;
+            }
+            return match bytes.get(pos) {
+                Some(&b) => Err(err(pos, ParseErrorKind::UnexpectedByte(b))),
+                None => Ok(clues),
+            };
+        }
+        let start = pos;
+        while bytes.get(pos).is_some_and(u8::is_ascii_alphabetic) {
+            pos += 1;
+        }
+        let pegs = &bytes[start..pos];
+        match bytes.get(pos) {
+            Some(b) if b.is_ascii_digit() => {}
+            Some(&b) if !b.is_ascii_whitespace() => {
+                return Err(err(pos, ParseErrorKind::UnexpectedByte(b)));
+            }
+            _ => return Err(err(pos, ParseErrorKind::MissingIndicator)),
+        }
+        if pegs.is_empty() {
+            return Err(err(pos, ParseErrorKind::MissingPegs));
+        }
+        if let Some(first) = clues.first()
+            && first.pegs.len() != pegs.len()
+        {
+            let expected = first.pegs.len();
+            // Point at the first extra peg, or where the missing pegs should be
+            let offset = if pegs.len() > expected {
+                start + expected
+            } else {
+                pos
+            };
+            return Err(err(
+                offset,
+                ParseErrorKind::InconsistentPegCount {
+                    expected,
+                    found: pegs.len(),
+                },
+            ));
+        }
+        let hearts_pos = pos;
+        let hearts = bytes[pos] - b'0';
+        pos += 1;
+        let dots = match bytes.get(pos) {
+            Some(&b) if b.is_ascii_digit() => b - b'0',
+            _ => return Err(err(pos, ParseErrorKind::MissingDots)),
+        };
+        pos += 1;
+        if usize::from(hearts) + usize::from(dots) > pegs.len() {
+            return Err(err(hearts_pos, ParseErrorKind::IndicatorTooLarge));
+        }
+        if let Some(&b) = bytes.get(pos)
+            && !b.is_ascii_whitespace()
+        {
+            return Err(err(pos, ParseErrorKind::UnexpectedByte(b)));
+        }
+        clues.push(Clue {
+            pegs: pegs.into(),
+            indicator: Indicator { dots, hearts },
+        });
+    }
+}
+
+#[test]
+fn test_parse_strict() {
+    let input = "ccprg12 cyppc11  crycg13\n";
+    assert_eq!(parse_strict(input), Ok(parse(input)));
+    let fail = |input, offset, clue, kind| {
+        assert_eq!(parse_strict(input), Err(ParseError { offset, clue, kind }));
+    };
+    fail("ccprg1 cyppc11", 6, 0, ParseErrorKind::MissingDots);
+    fail("ccprg12 cyppc", 13, 1, ParseErrorKind::MissingIndicator);
+    fail(
+        "ccprg12, cyppc11",
+        7,
+        0,
+        ParseErrorKind::UnexpectedByte(b','),
+    );
+    fail(
+        "ccprg12 cy.ppc11",
+        10,
+        1,
+        ParseErrorKind::UnexpectedByte(b'.'),
+    );
+    fail("ccprg12 11", 8, 1, ParseErrorKind::MissingPegs);
+    fail(
+        "ccprg12 cyppcc11",
+        13,
+        1,
+        ParseErrorKind::InconsistentPegCount {
+            expected: 5,
+            found: 6,
+        },
+    );
+    fail(
+        "ccprg12 cypp11",
+        12,
+        1,
+        ParseErrorKind::InconsistentPegCount {
+            expected: 5,
+            found: 4,
+        },
+    );
+    fail("ccprg42", 5, 0, ParseErrorKind::IndicatorTooLarge);
+    fail("ccprg123", 7, 0, ParseErrorKind::UnexpectedByte(b'3'));
+    // Free pegs are only for whole puzzles
+    fail("ccprg12 +bw", 8, 1, ParseErrorKind::UnexpectedByte(b'+'));
+    let fail_puzzle = |input, offset, clue, kind| {
+        assert_eq!(parse_puzzle(input), Err(ParseError { offset, clue, kind }));
+    };
+    fail_puzzle("ccprg12 +", 9, 1, ParseErrorKind::MissingFreePegs);
+    fail_puzzle("ccprg12 +b1", 10, 1, ParseErrorKind::UnexpectedByte(b'1'));
+    fail_puzzle(
+        "ccprg12 +b cyppc11",
+        11,
+        1,
+        ParseErrorKind::UnexpectedByte(b'c'),
+    );
+    fail_puzzle("ccprg12 +b +w", 11, 1, ParseErrorKind::UnexpectedByte(b'+'));
+}
+
+#[test]
+fn test_round_trip() {
+    for n_pegs in crate::SUPPORTED_PEG_COUNTS {
+        let clues: Vec<Clue> = (0..n_pegs)
+            .map(|i| {
+                let pegs: Vec<u8> = (0..n_pegs).map(|j| b"cgrpyb"[(i + j) % 6]).collect();
+                let hearts = u8::try_from(i / 2).unwrap();
+                let dots = u8::try_from((n_pegs - i) / 2).unwrap();
+                Clue {
+                    pegs: pegs.into(),
+                    indicator: Indicator { dots, hearts },
+                }
+            })
+            .collect();
+        let short = serialize(&clues);
+        assert_eq!(parse(&short), clues);
+        assert_eq!(parse_strict(&short), Ok(clues));
+    }
+    let input = "ccprg12 cyppc11 crycg13 rccgg13 yrccc03";
+    assert_eq!(serialize(&parse(input)), input);
+    let mut puzzle = Puzzle::from_clues(parse(input));
+    assert_eq!(serialize_puzzle(&puzzle), input);
+    puzzle.free_pegs = b"bw".to_vec();
+    let short = serialize_puzzle(&puzzle);
+    assert_eq!(short, format!("{input} +bw"));
+    assert_eq!(parse_puzzle(&short), Ok(puzzle));
+}
@@ -0,0 +1,217 @@
+//! Ranks possible next guesses by how much they are expected to narrow down the solutions.
+//!
+//! Every guess splits the remaining solutions into groups, one for each indicator it could get.
+//! Only the group matching the indicator the game actually shows survives.
+
+use crate::{Peg, Puzzle, SolveError, bruteforce::compare};
+use std::cmp::Ordering;
+
+/// Upper limit on guess/solution comparisons, to keep suggestions for large puzzles responsive.
+///
+/// When there would be more, fewer guesses are considered.
+const MAX_COMPARISONS: usize = 4_000_000;
+
+/// Most solutions kept to score guesses against. Beyond that, an evenly spaced sample
+/// stands in for all of them.
+///
+/// Scoring every kept solution as a guess against all of them stays within
+/// [`MAX_COMPARISONS`].
+const MAX_SAMPLE: usize = MAX_COMPARISONS.isqrt();
+
+/// How [`suggest_guess`] decides which guess is best
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestStrategy {
+    /// Knuth's minimax: the fewest remaining solutions in the worst case
+    Minimax,
+    /// The fewest remaining solutions on average
+    ExpectedSize,
+    /// The most information gained on average
+    Entropy,
+}
+
+/// What a guess is expected to achieve
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuessScore {
+    pub guess: Vec<Peg>,
+    /// Whether the guess could itself be the solution
+    pub possible_solution: bool,
+    /// Remaining solutions after the guess, in the worst case
+    pub worst_case: usize,
+    /// Remaining solutions after the guess, on average
+    pub expected: f64,
+    /// Information gained by the guess on average, in bits
+    pub entropy: f64,
+}
+
+/// Scores possible next guesses for `puzzle`, best first according to `strategy`.
+///
+/// Every solution is assumed to be equally likely. Guessing the solution itself leaves
+/// no remaining solutions. All guesses allowed by the rules are considered if that's cheap
+/// enough, otherwise only the remaining solutions (or an evenly spaced selection of them).
+/// With thousands of solutions, guesses are scored against a sample of them, so the
+/// scores are estimates.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn suggest_guess(
+    puzzle: &Puzzle,
+    strategy: SuggestStrategy,
+) -> Result<Vec<GuessScore>, SolveError> {
+    let (solutions, weight) = sample(puzzle.solutions_backtrack()?);
+    if solutions.len() <= 1 && weight == 1 {
+        return Ok(solutions
+            .into_iter()
+            .map(|guess| GuessScore {
+                guess,
+                possible_solution: true,
+                worst_case: 0,
+                expected: 0.0,
+                entropy: 0.0,
+            })
+            .collect());
+    }
+    let guesses: Vec<Vec<Peg>> = if let Ok(candidates) = puzzle.candidates()
+        && candidates.len().saturating_mul(solutions.len()) <= MAX_COMPARISONS
+    {
+        let no_clues = Puzzle {
+            clues: Vec::new(),
+            ..puzzle.clone()
+        };
+        candidates
+            .filter(|guess| no_clues.is_solution(guess))
+            .collect()
+    } else {
+        // There are at most `MAX_SAMPLE` of them, so this is within `MAX_COMPARISONS`
+        solutions.clone()
+    };
+    let mut scores: Vec<GuessScore> = guesses
+        .into_iter()
+        .map(|guess| score(guess, &solutions, weight, puzzle.size))
+        .collect();
+    scores.sort_by(|a, b| rank(strategy, a, b));
+    Ok(scores)
+}
+
+/// At most [`MAX_SAMPLE`] of `solutions`, evenly spaced, and how many solutions each
+/// stands for.
+///
+/// Keeps every `weight`th solution, doubling `weight` whenever the sample is full.
+fn sample(solutions: impl Iterator<Item = Vec<Peg>>) -> (Vec<Vec<Peg>>, usize) {
+    let mut sample = Vec::new();
+    let mut weight = 1;
+    for (i, solution) in solutions.enumerate() {
+        if i % weight != 0 {
+            continue;
+        }
+        if sample.len() == MAX_SAMPLE {
+            let mut keep = false;
+            sample.retain(|_| {
+                keep = !keep;
+                keep
+            });
+            weight *= 2;
+            if i % weight != 0 {
+                continue;
+            }
+        }
+        sample.push(solution);
+    }
+    (sample, weight)
+}
+
+/// Scores `guess` against `solutions`, each of which stands for `weight` solutions
+// Solution counts are nowhere near 2^52
+#[expect(clippy::cast_precision_loss)]
+fn score(guess: Vec<Peg>, solutions: &[Vec<Peg>], weight: usize, size: usize) -> GuessScore {
+    // Group sizes, indexed by `hearts * (size + 1) + dots`
+    let mut groups = vec![0usize; (size + 1) * (size + 1)];
+    let mut possible_solution = false;
+    for solution in solutions {
+        let indicator = compare(&guess, solution);
+        if usize::from(indicator.hearts) == size {
+            // Solved, nothing remains
+            possible_solution = true;
+            continue;
+        }
+        groups[usize::from(indicator.hearts) * (size + 1) + usize::from(indicator.dots)] += weight;
+    }
+    let total = (solutions.len() * weight) as f64;
+    let mut expected = 0.0;
+    let mut entropy = if possible_solution {
+        total.log2() / total
+    } else {
+        0.0
+    };
+    for &group in groups.iter().filter(|&&group| group != 0) {
+        let p = group as f64 / total;
+        expected += p * group as f64;
+        entropy -= p * p.log2();
+    }
+    GuessScore {
+        guess,
+        possible_solution,
+        worst_case: groups.iter().copied().max().unwrap_or(0),
+        expected,
+        entropy,
+    }
+}
+
+/// Orders `a` before `b` if it's the better guess.
+///
+/// Ties are broken in favor of guesses that could be the solution.
+fn rank(strategy: SuggestStrategy, a: &GuessScore, b: &GuessScore) -> Ordering {
+    let by_strategy = match strategy {
+        SuggestStrategy::Minimax => a
+            .worst_case
+            .cmp(&b.worst_case)
+            .then(a.expected.total_cmp(&b.expected)),
+        SuggestStrategy::ExpectedSize => a.expected.total_cmp(&b.expected),
+        SuggestStrategy::Entropy => b.entropy.total_cmp(&a.entropy),
+    };
+    by_strategy.then(b.possible_solution.cmp(&a.possible_solution))
+}
+
+#[test]
+fn test_suggest() {
+    use crate::parse_shortform;
+    let puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13"));
+    assert_eq!(puzzle.solutions().unwrap().count(), 10);
+    for strategy in [
+        SuggestStrategy::Minimax,
+        SuggestStrategy::ExpectedSize,
+        SuggestStrategy::Entropy,
+    ] {
+        let scores = suggest_guess(&puzzle, strategy).unwrap();
+        // Every guess allowed by the rules was considered
+        assert_eq!(scores.len(), 5usize.pow(5));
+        let best = &scores[0];
+        assert!(best.worst_case < 10);
+        assert!(best.expected < 10.0);
+        assert!(best.entropy > 0.0);
+        assert!(
+            scores
+                .windows(2)
+                .all(|w| rank(strategy, &w[0], &w[1]) != Ordering::Greater)
+        );
+    }
+    let solved = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03"));
+    let scores = suggest_guess(&solved, SuggestStrategy::Minimax).unwrap();
+    assert_eq!(scores.len(), 1);
+    assert_eq!(scores[0].guess, b"cgrgy");
+}
+
+#[test]
+fn test_sample() {
+    let (all, weight) = sample((0..MAX_SAMPLE).map(|i| i.to_be_bytes().to_vec()));
+    assert_eq!((all.len(), weight), (MAX_SAMPLE, 1));
+    let (thinned, weight) = sample((0..5000usize).map(|i| i.to_be_bytes().to_vec()));
+    assert_eq!(weight, 4);
+    assert_eq!(thinned.len(), 1250);
+    assert!(
+        thinned
+            .iter()
+            .enumerate()
+            .all(|(i, solution)| *solution == (i * 4).to_be_bytes())
+    );
+}
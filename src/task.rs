@@ -0,0 +1,181 @@
+//! Solving a bit at a time, for searches that take long enough to need watching.
+//!
+//! A [`SolveTask`] goes through the brute-force candidates in chunks of a given size, so
+//! a game loop can run one chunk per frame. [`solve_with_progress`] runs a task to the end,
+//! reporting on the way, and stops early when its [`CancelToken`] is cancelled.
+
+use crate::{
+    Clue, Peg, Puzzle, Rules, SolveError, bruteforce::validate_guess, combinations::SliceCombo,
+};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// How many candidates [`solve_with_progress`] checks between reports
+const PROGRESS_INTERVAL: usize = 1 << 16;
+
+/// How far a solve has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Candidates checked so far
+    pub checked: usize,
+    /// Candidates there are in total
+    pub total: usize,
+    /// Solutions found so far
+    pub found: usize,
+}
+
+impl Progress {
+    /// How much of the search is done, from 0 to 1
+    #[must_use]
+    pub fn fraction(&self) -> f64 {
+        // Precision only matters for showing it
+        #[expect(clippy::cast_precision_loss)]
+        if self.total == 0 {
+            1.0
+        } else {
+            self.checked as f64 / self.total as f64
+        }
+    }
+}
+
+/// Asks a running solve to stop. Clones share the same flag, so one can be handed to
+/// another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A brute-force solve that runs as many candidates at a time as it's told to
+pub struct SolveTask {
+    clues: Vec<Clue>,
+    rules: Rules,
+    combos: SliceCombo<Peg, Vec<Peg>>,
+    total: usize,
+    /// In ascending order
+    solutions: Vec<Vec<Peg>>,
+}
+
+impl SolveTask {
+    /// A task for the solutions of `puzzle`, that hasn't checked anything yet
+    ///
+    /// # Errors
+    ///
+    /// If the puzzle doesn't [validate](Puzzle::validate), or has
+    /// [too many candidates](SolveError::TooManyCandidates)
+    pub fn new(puzzle: &Puzzle) -> Result<Self, SolveError> {
+        puzzle.validate()?;
+        let combos = puzzle.candidates()?;
+        Ok(Self {
+            clues: puzzle.clues.clone(),
+            rules: puzzle.rules,
+            total: combos.size_hint().0,
+            combos,
+            solutions: Vec::new(),
+        })
+    }
+    /// Checks up to `budget` more candidates, and returns whether the task is finished
+    pub fn step(&mut self, budget: usize) -> bool {
+        for _ in 0..budget {
+            let Some(guess) = self.combos.next_ref() else {
+                break;
+            };
+            if validate_guess(guess, &self.clues, self.rules) {
+                self.solutions.push(guess.to_vec());
+            }
+        }
+        self.is_finished()
+    }
+    /// Whether every candidate has been checked
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.combos.size_hint().0 == 0
+    }
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        Progress {
+            checked: self.total - self.combos.size_hint().0,
+            total: self.total,
+            found: self.solutions.len(),
+        }
+    }
+    /// The solutions found so far, in ascending order
+    #[must_use]
+    pub fn solutions(&self) -> &[Vec<Peg>] {
+        &self.solutions
+    }
+    #[must_use]
+    pub fn into_solutions(self) -> Vec<Vec<Peg>> {
+        self.solutions
+    }
+}
+
+/// All solutions of `puzzle`, like [`Puzzle::solutions`], calling `report` now and then
+/// with how far it got.
+///
+/// Returns `None` if `cancel` was cancelled before the search was done.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate), or has
+/// [too many candidates](SolveError::TooManyCandidates)
+pub fn solve_with_progress(
+    puzzle: &Puzzle,
+    cancel: &CancelToken,
+    mut report: impl FnMut(Progress),
+) -> Result<Option<Vec<Vec<Peg>>>, SolveError> {
+    let mut task = SolveTask::new(puzzle)?;
+    loop {
+        if cancel.is_cancelled() {
+            return Ok(None);
+        }
+        let finished = task.step(PROGRESS_INTERVAL);
+        report(task.progress());
+        if finished {
+            return Ok(Some(task.into_solutions()));
+        }
+    }
+}
+
+#[test]
+fn test_task() {
+    use crate::parse_shortform;
+    let mut puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13"));
+    puzzle.free_pegs = b"bw".to_vec();
+    let expected: Vec<_> = puzzle.solutions().unwrap().collect();
+    let mut task = SolveTask::new(&puzzle).unwrap();
+    assert_eq!(task.progress().total, 7usize.pow(5));
+    let mut steps = 0;
+    while !task.step(1000) {
+        steps += 1;
+        let progress = task.progress();
+        assert_eq!(progress.checked, steps * 1000);
+        assert_eq!(progress.found, task.solutions().len());
+        assert!(expected.starts_with(task.solutions()));
+    }
+    assert!(task.progress().fraction() >= 1.0);
+    assert_eq!(task.into_solutions(), expected);
+    let mut reports = Vec::new();
+    let cancel = CancelToken::new();
+    assert_eq!(
+        solve_with_progress(&puzzle, &cancel, |p| reports.push(p)),
+        Ok(Some(expected))
+    );
+    assert_eq!(reports.last().map(|p| p.checked), Some(7usize.pow(5)));
+    // Clones share the flag
+    cancel.clone().cancel();
+    assert_eq!(solve_with_progress(&puzzle, &cancel, |_| {}), Ok(None));
+}
@@ -0,0 +1,112 @@
+//! Solving with some room for mistakes in the clues.
+//!
+//! Indicators read off a screenshot are easy to get wrong, mostly by taking a heart for a dot
+//! or the other way around. Allowing a few clues to disagree with the solution
+//! still finds the right answer in that case, and tells which clues were misread.
+
+use crate::{Indicator, Peg, Puzzle, SolveError, backtrack::Backtrack, bruteforce::compare};
+
+/// A clue that a near-solution disagrees with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Index of the clue
+    pub clue: usize,
+    /// The indicator the clue has
+    pub expected: Indicator,
+    /// The indicator the clue would have if the near-solution was the solution
+    pub actual: Indicator,
+}
+
+impl Mismatch {
+    /// How far off the clue's indicator is: |Δhearts| + |Δdots|
+    #[must_use]
+    pub fn distance(&self) -> u32 {
+        u32::from(self.expected.hearts.abs_diff(self.actual.hearts))
+            + u32::from(self.expected.dots.abs_diff(self.actual.dots))
+    }
+}
+
+/// A guess that would be a solution if a few clues were different
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearSolution {
+    pub guess: Vec<Peg>,
+    /// The clues the guess disagrees with, in clue order
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl NearSolution {
+    /// Total distance of all mismatched indicators
+    #[must_use]
+    pub fn distance(&self) -> u32 {
+        self.mismatches.iter().map(Mismatch::distance).sum()
+    }
+}
+
+/// Finds the guesses that disagree with at most `max_mismatches` clues of `puzzle`.
+///
+/// The rules are never relaxed, only the clues.
+/// Results are ordered by number of mismatched clues, then by total distance,
+/// then by their pegs, so actual solutions come first.
+///
+/// Every extra allowed mismatch makes for a lot more results, so keep `max_mismatches` small.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn solve_tolerant(
+    puzzle: &Puzzle,
+    max_mismatches: usize,
+) -> Result<Vec<NearSolution>, SolveError> {
+    puzzle.validate()?;
+    let mut search = Backtrack::for_puzzle(puzzle).with_tolerance(max_mismatches);
+    let mut near = Vec::new();
+    while search.advance() {
+        let guess = &search.guess;
+        let mismatches = puzzle
+            .clues
+            .iter()
+            .enumerate()
+            .filter_map(|(clue, c)| {
+                let actual = compare(guess, &c.pegs);
+                (actual != c.indicator).then_some(Mismatch {
+                    clue,
+                    expected: c.indicator,
+                    actual,
+                })
+            })
+            .collect();
+        near.push(NearSolution {
+            guess: guess.clone(),
+            mismatches,
+        });
+    }
+    // Stable, so the pegs stay in order within a rank
+    near.sort_by_key(|n| (n.mismatches.len(), n.distance()));
+    Ok(near)
+}
+
+#[test]
+fn test_tolerant() {
+    use crate::parse_shortform;
+    let puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc03"));
+    let exact = solve_tolerant(&puzzle, 0).unwrap();
+    assert_eq!(exact.len(), 1);
+    assert_eq!(exact[0].guess, b"cgrgy");
+    assert!(exact[0].mismatches.is_empty());
+    // The last clue with a dot misread as a heart
+    let misread = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13 rccgg13 yrccc12"));
+    assert_eq!(misread.solve(), Ok(None));
+    let near = solve_tolerant(&misread, 1).unwrap();
+    assert!(near.is_sorted_by_key(|n| (n.mismatches.len(), n.distance())));
+    let found = near.iter().find(|n| n.guess == b"cgrgy").unwrap();
+    assert_eq!(
+        found.mismatches,
+        [Mismatch {
+            clue: 4,
+            expected: Indicator { dots: 2, hearts: 1 },
+            actual: Indicator { dots: 3, hearts: 0 },
+        }]
+    );
+    assert_eq!(found.distance(), 2);
+    assert!(near.iter().all(|n| n.mismatches.len() == 1));
+}
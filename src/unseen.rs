@@ -0,0 +1,119 @@
+//! Groups solutions that only differ in which unseen pegs they use.
+//!
+//! A peg that no clue has can't be told apart from any other such peg by the clues,
+//! so with a big palette, solutions often come in large families that all look the same.
+
+use crate::{Peg, Puzzle, SolveError, backtrack::Backtrack};
+use std::{collections::HashMap, fmt};
+
+/// One slot of a [`SolutionGroup`] pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternPeg {
+    /// This very peg, which some clue has
+    Seen(Peg),
+    /// Some peg that no clue has.
+    ///
+    /// Slots with the same number hold the same peg, and slots with different numbers
+    /// hold different pegs. Numbers count from 0, in order of first appearance.
+    Unseen(usize),
+}
+
+/// Solutions that are the same, except for which unseen pegs they use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionGroup {
+    pub pattern: Vec<PatternPeg>,
+    /// In ascending order
+    pub solutions: Vec<Vec<Peg>>,
+}
+
+/// Shows the pattern, with the unseen pegs numbered from 1
+impl fmt::Display for SolutionGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for peg in &self.pattern {
+            match *peg {
+                PatternPeg::Seen(peg) => write!(f, "{}", char::from(peg))?,
+                PatternPeg::Unseen(n) => write!(f, "{}", n + 1)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// All solutions of `puzzle`, grouped by which pegs are [unseen](Puzzle::unseen_pegs).
+///
+/// Groups are in the order of their first solution.
+///
+/// # Errors
+///
+/// If the puzzle doesn't [validate](Puzzle::validate)
+pub fn group_solutions(puzzle: &Puzzle) -> Result<Vec<SolutionGroup>, SolveError> {
+    puzzle.validate()?;
+    let unseen = puzzle.unseen_pegs();
+    let mut groups: Vec<SolutionGroup> = Vec::new();
+    let mut index = HashMap::new();
+    let mut search = Backtrack::for_puzzle(puzzle);
+    while search.advance() {
+        let solution = &search.guess;
+        let mut numbered: Vec<Peg> = Vec::new();
+        let pattern: Vec<PatternPeg> = solution
+            .iter()
+            .map(|&peg| {
+                if !unseen.contains(&peg) {
+                    return PatternPeg::Seen(peg);
+                }
+                let n = numbered.iter().position(|&p| p == peg).unwrap_or_else(|| {
+                    numbered.push(peg);
+                    numbered.len() - 1
+                });
+                PatternPeg::Unseen(n)
+            })
+            .collect();
+        let i = *index.entry(pattern.clone()).or_insert_with(|| {
+            groups.push(SolutionGroup {
+                pattern,
+                solutions: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        groups[i].solutions.push(solution.clone());
+    }
+    Ok(groups)
+}
+
+#[test]
+fn test_group_solutions() {
+    use crate::parse_shortform;
+    let mut puzzle = Puzzle::from_clues(parse_shortform("ccprg12 cyppc11 crycg13"));
+    puzzle.palette = Some(b"cgprywkb".to_vec());
+    assert_eq!(puzzle.unseen_pegs(), b"bkw");
+    let solutions: Vec<_> = puzzle.solutions_backtrack().unwrap().collect();
+    // Without the unseen pegs, there are 10
+    assert!(solutions.len() > 10);
+    let groups = group_solutions(&puzzle).unwrap();
+    assert_eq!(
+        groups.iter().map(|g| g.solutions.len()).sum::<usize>(),
+        solutions.len()
+    );
+    for group in &groups {
+        assert!(group.solutions.is_sorted());
+        let unseen_slots = group.to_string().bytes().filter(u8::is_ascii_digit).count();
+        if unseen_slots == 0 {
+            assert_eq!(group.solutions.len(), 1);
+        }
+    }
+    let with_unseen = groups.iter().find(|g| g.to_string() == "cgr1y").unwrap();
+    assert_eq!(with_unseen.solutions, [b"cgrby", b"cgrky", b"cgrwy"]);
+    puzzle.palette = Some(b"cgpry".to_vec());
+    assert_eq!(puzzle.validate(), Ok(()));
+    puzzle.palette = Some(b"cgpr".to_vec());
+    assert_eq!(
+        puzzle.validate(),
+        Err(SolveError::PegNotInPalette { clue: 1, peg: b'y' })
+    );
+    puzzle.palette = Some(b"cgpry".to_vec());
+    puzzle.free_pegs = b"bw".to_vec();
+    assert_eq!(
+        puzzle.validate(),
+        Err(SolveError::FreePegNotInPalette(b'b'))
+    );
+}